@@ -10,7 +10,7 @@ use cortex_m_rt::entry;
 //
 // A breakpoint can be set on `rust_begin_unwind` to catch panics.
 //
-use pa_spl::PaSpl;
+use pa_spl::{hal::Eh0, PaSpl};
 use panic_halt as _;
 use stm32f3xx_hal::{delay::Delay, i2c::I2c, pac, prelude::*, serial::config, serial::Serial};
 
@@ -152,9 +152,10 @@ fn main() -> ! {
         &mut rcc.apb1,
     );
 
-    // Use the I2C1 instance to create an instance of PaSpl.
+    // Use the I2C1 instance to create an instance of PaSpl. The HAL implements
+    // embedded-hal 0.2, so the instance is wrapped in Eh0.
     //
-    let mut pa_spl = PaSpl::new(i2c);
+    let mut pa_spl = PaSpl::new(Eh0(i2c));
 
     // Create a buffer able to be converted to a string.
     //
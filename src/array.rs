@@ -0,0 +1,176 @@
+//! Several modules at different addresses on one I2C bus.
+
+use crate::hal::I2cBus;
+use crate::{Error, PaSpl, Version};
+
+/// `N` PA SPL Modules at different I2C addresses on the I2C bus `I2C`.
+///
+/// A single driver is switched between the addresses, so the modules are
+/// accessed one after the other. Every operation on the array is attempted on
+/// every module and returns one result per module, in the order of the
+/// addresses, so a module that does not respond does not stop the others from
+/// being read.
+pub struct PaSplArray<I2C, const N: usize>
+where
+    I2C: I2cBus,
+{
+    driver: PaSpl<I2C>,
+    addrs: [u8; N],
+    /// VERSION register value of each module, cached on first use.
+    versions: [Option<Version>; N],
+}
+
+impl<E, I2C, const N: usize> PaSplArray<I2C, N>
+where
+    I2C: I2cBus<Error = E>,
+{
+    /// Initializes the modules at the I2C addresses `addrs`.
+    ///
+    pub fn new(i2c: I2C, addrs: [u8; N]) -> Self {
+        Self {
+            driver: PaSpl::new(i2c),
+            addrs,
+            versions: [None; N],
+        }
+    }
+
+    /// Gets the I2C addresses of the modules.
+    ///
+    pub fn addresses(&self) -> &[u8; N] {
+        &self.addrs
+    }
+
+    /// Runs `f` on the driver of every module.
+    ///
+    /// Use this to apply the same configuration to all modules. `f` is called
+    /// for every module even if it fails for some of them.
+    ///
+    pub fn configure<F>(&mut self, mut f: F) -> [Result<(), Error<E>>; N]
+    where
+        F: FnMut(&mut PaSpl<I2C>) -> Result<(), Error<E>>,
+    {
+        self.for_each(|pa_spl| f(pa_spl))
+    }
+
+    /// Gets the latest SPL value in decibels of every module.
+    ///
+    pub fn read_decibels(&mut self) -> [Result<u8, Error<E>>; N] {
+        self.for_each(PaSpl::get_latest_decibel)
+    }
+
+    /// Runs `f` on the driver of every module and returns the result for each
+    /// module.
+    ///
+    pub fn for_each<T, F>(&mut self, mut f: F) -> [Result<T, Error<E>>; N]
+    where
+        F: FnMut(&mut PaSpl<I2C>) -> Result<T, Error<E>>,
+    {
+        core::array::from_fn(|index| f(self.select(index)))
+    }
+
+    /// Gets the driver of the module at position `index` of the addresses.
+    ///
+    /// Returns `None` if `index` is out of range.
+    ///
+    pub fn get(&mut self, index: usize) -> Option<&mut PaSpl<I2C>> {
+        if index < N {
+            Some(self.select(index))
+        } else {
+            None
+        }
+    }
+
+    /// Destroys this array and releases the I2C bus.
+    ///
+    pub fn release(self) -> I2C {
+        self.driver.release()
+    }
+
+    /// Points the driver at the module at position `index`, keeping the cached
+    /// version of the module it was pointed at.
+    ///
+    fn select(&mut self, index: usize) -> &mut PaSpl<I2C> {
+        if let Some(current) = self
+            .addrs
+            .iter()
+            .position(|&a| a == self.driver.device_addr)
+        {
+            self.versions[current] = self.driver.version;
+        }
+        self.driver.device_addr = self.addrs[index];
+        self.driver.version = self.versions[index];
+        &mut self.driver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PaSplArray;
+    use crate::mock::{with_bus_error, I2cMock, I2cTransaction};
+    use crate::{AveragingTime, Error, Variant, REG_DECIBEL, REG_TAVG_HIGH, REG_VERSION};
+
+    const ADDRS: [u8; 3] = [0x48, 0x49, 0x4a];
+
+    #[test]
+    fn confirm_read_decibels_continues_past_nack() {
+        let expectations = vec![
+            I2cTransaction::write_read(ADDRS[0], vec![REG_DECIBEL], vec![55]),
+            with_bus_error(I2cTransaction::write_read(
+                ADDRS[1],
+                vec![REG_DECIBEL],
+                vec![0],
+            )),
+            I2cTransaction::write_read(ADDRS[2], vec![REG_DECIBEL], vec![71]),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut array = PaSplArray::new(i2c_mock, ADDRS);
+
+        let [first, second, third] = array.read_decibels();
+        assert_eq!(Ok(55), first);
+        assert!(matches!(second, Err(Error::I2c { .. })));
+        assert_eq!(Ok(71), third);
+
+        let mut mock = array.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_configure() {
+        let expectations: Vec<I2cTransaction> = ADDRS
+            .iter()
+            .map(|&addr| I2cTransaction::write(addr, vec![REG_TAVG_HIGH, 0x00, 0x7D]))
+            .collect();
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut array = PaSplArray::new(i2c_mock, ADDRS);
+
+        let results = array.configure(|pa_spl| pa_spl.set_avg_time(AveragingTime::FAST));
+        assert!(results.iter().all(Result::is_ok));
+
+        let mut mock = array.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_versions_cached_per_module() {
+        let expectations = vec![
+            I2cTransaction::write_read(ADDRS[0], vec![REG_VERSION], vec![0x31]),
+            I2cTransaction::write_read(ADDRS[1], vec![REG_VERSION], vec![0x32]),
+            I2cTransaction::write_read(ADDRS[2], vec![REG_VERSION], vec![0x81]),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut array = PaSplArray::new(i2c_mock, ADDRS);
+
+        let expected = [
+            Ok(Variant::MemsLts),
+            Ok(Variant::MemsSpectrumAnalyzer),
+            Ok(Variant::ExternalMic),
+        ];
+        assert_eq!(expected, array.for_each(|pa_spl| pa_spl.get_variant()));
+        // The versions are cached, so the variants are known without a read.
+        assert_eq!(expected, array.for_each(|pa_spl| pa_spl.get_variant()));
+        assert!(array.get(3).is_none());
+
+        let mut mock = array.release();
+        mock.done();
+    }
+}
@@ -0,0 +1,1094 @@
+//! Async driver for embedded-hal-async.
+
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{self, I2c};
+
+use crate::config::invalid_as_none;
+use crate::self_test::ALTERNATING_PATTERNS;
+use crate::{
+    compare_written, has_reserved_bits, is_readable_range, is_writable_range, verify_mask,
+    AveragingTime, Config, ConfigDiff, ControlRegister, Error, Gain, History, Operation,
+    ResetRegister, SelfTestReport, Spectrum, Variant, Version, CONTROL_RESERVED_MASK,
+    DEVICE_ADDR_DEFAULT, HISTORY_LEN, REGS_DEVICE_ID, REG_CONTROL, REG_DBHISTORY_0, REG_DECIBEL,
+    REG_FREQ_64BINS_0, REG_GAIN, REG_MAX, REG_MIN, REG_RESET, REG_SCRATCH, REG_TAVG_HIGH,
+    REG_THR_MAX, REG_THR_MIN, REG_VERSION, SPECTRUM_BINS,
+};
+
+/// A PA SPL Module on the async I2C bus `I2C`.
+///
+/// The async twin of [`PaSpl`](crate::PaSpl), with the same methods awaiting
+/// the bus instead of blocking on it. The types built around the blocking
+/// driver, such as [`PaSplBuilder`](crate::PaSplBuilder),
+/// [`SleepingPaSpl`](crate::SleepingPaSpl), [`ClockedPaSpl`](crate::ClockedPaSpl)
+/// and [`Retry`](crate::Retry), have no async twins.
+pub struct PaSplAsync<I2C>
+where
+    I2C: I2c,
+{
+    i2c: I2C,
+    device_addr: u8,
+    /// VERSION register value, cached on first use.
+    version: Option<Version>,
+    /// Read back and compare writes to configuration registers.
+    verify_writes: bool,
+}
+
+impl<E, I2C> PaSplAsync<I2C>
+where
+    I2C: I2c<Error = E>,
+{
+    /// Initializes the PCB Artists SPL Module driver.
+    ///
+    pub fn new(i2c: I2C) -> Self {
+        Self {
+            i2c,
+            device_addr: DEVICE_ADDR_DEFAULT,
+            version: None,
+            verify_writes: false,
+        }
+    }
+
+    /// Enables or disables verification of configuration writes.
+    ///
+    /// See [`PaSpl::set_verify_writes`](crate::PaSpl::set_verify_writes).
+    ///
+    pub fn set_verify_writes(&mut self, enable: bool) {
+        self.verify_writes = enable;
+    }
+
+    /// Returns true if configuration writes are verified.
+    ///
+    pub fn verify_writes(&self) -> bool {
+        self.verify_writes
+    }
+
+    /// Sets a new I2C device address.
+    ///
+    /// See [`PaSpl::set_device_addr`](crate::PaSpl::set_device_addr).
+    ///
+    pub fn set_device_addr(&mut self, addr: u8) {
+        self.device_addr = addr;
+        self.version = None;
+    }
+
+    /// Clears the history registers.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn clear_history(&mut self) -> Result<(), Error<E>> {
+        self.set_reset_register(ResetRegister::new().with_clear_history(true))
+            .await
+    }
+
+    /// Clears a pending interrupt.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn clear_interrupt(&mut self) -> Result<(), Error<E>> {
+        self.set_reset_register(ResetRegister::new().with_clear_interrupt(true))
+            .await
+    }
+
+    /// Clears the MIN and MAX registers.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn clear_min_max(&mut self) -> Result<(), Error<E>> {
+        self.set_reset_register(ResetRegister::new().with_clear_min_max(true))
+            .await
+    }
+
+    /// Gets the averaging time from registers TAVG high and TAVG low.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidRegisterValue`] if the registers hold less than
+    /// 10 ms or more than 10,000 ms.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn get_avg_time(&mut self) -> Result<AveragingTime, Error<E>> {
+        let mut buffer: [u8; 2] = [0; 2];
+        self.read_bytes(REG_TAVG_HIGH, &mut buffer).await?;
+        let avg_time_ms = u16::from_be_bytes(buffer);
+        AveragingTime::from_ms(avg_time_ms).ok_or(Error::InvalidRegisterValue {
+            register: REG_TAVG_HIGH,
+            value: avg_time_ms,
+        })
+    }
+
+    /// Gets the CONTROL register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn get_control_register(&mut self) -> Result<ControlRegister, Error<E>> {
+        let control_reg_raw = self.read_byte(REG_CONTROL).await?;
+        Ok(ControlRegister::from_bits(control_reg_raw))
+    }
+
+    /// Gets the 32-bit device ID from registers ID3-ID0.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn get_device_id(&mut self) -> Result<u32, Error<E>> {
+        let mut buffer: [u8; 4] = [0; 4];
+        self.read_bytes(REGS_DEVICE_ID[0], &mut buffer).await?;
+        Ok(u32::from_be_bytes(buffer))
+    }
+
+    /// Gets the firmware version from the VERSION register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn get_firmware_version(&mut self) -> Result<u8, Error<E>> {
+        self.get_version().await.map(Version::into_bits)
+    }
+
+    /// Gets the hardware and firmware version from the VERSION register.
+    ///
+    /// The version is cached for use by the driver.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn get_version(&mut self) -> Result<Version, Error<E>> {
+        let version = Version::from_bits(self.read_byte(REG_VERSION).await?);
+        self.version = Some(version);
+        Ok(version)
+    }
+
+    /// Gets the product variant of the module.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn get_variant(&mut self) -> Result<Variant, Error<E>> {
+        match self.version {
+            Some(version) => Ok(version.variant()),
+            None => self.get_version().await.map(Version::variant),
+        }
+    }
+
+    /// Gets the microphone gain from the GAIN register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Unsupported`] if the variant has no adjustable gain.
+    ///
+    /// Returns [`Error::InvalidRegisterValue`] if the register holds more than
+    /// 95 steps.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn get_gain(&mut self) -> Result<Gain, Error<E>> {
+        self.require(Variant::has_gain).await?;
+        let steps = self.read_byte(REG_GAIN).await?;
+        Gain::from_steps(steps).ok_or(Error::InvalidRegisterValue {
+            register: REG_GAIN,
+            value: steps as u16,
+        })
+    }
+
+    /// Gets the state of the line output from the CONTROL register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Unsupported`] if the variant has no line output.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn get_line_out(&mut self) -> Result<bool, Error<E>> {
+        self.require(Variant::has_line_out).await?;
+        Ok(self.get_control_register().await?.line_out())
+    }
+
+    /// Gets the decibel history from registers DBHISTORY_0 to DBHISTORY_99
+    /// in a single burst read.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn read_history(&mut self) -> Result<History, Error<E>> {
+        self.read_history_range(0, HISTORY_LEN).await
+    }
+
+    /// Gets `len` entries of the decibel history starting at DBHISTORY_`start`
+    /// in a single burst read.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidArgument`] if `len` is 0 or the range extends
+    /// past DBHISTORY_99.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn read_history_range(
+        &mut self,
+        start: usize,
+        len: usize,
+    ) -> Result<History, Error<E>> {
+        if len == 0 || start.saturating_add(len) > HISTORY_LEN {
+            return Err(Error::InvalidArgument);
+        }
+
+        let mut buffer: [u8; HISTORY_LEN] = [0; HISTORY_LEN];
+        self.read_bytes(REG_DBHISTORY_0 + start as u8, &mut buffer[..len])
+            .await?;
+
+        Ok(History::from_slice(start, &buffer[..len]))
+    }
+
+    /// Gets the latest SPL value in decibels from the DECIBEL register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn get_latest_decibel(&mut self) -> Result<u8, Error<E>> {
+        self.read_byte(REG_DECIBEL).await
+    }
+
+    /// Gets the max SPL value in decibels from the MAX register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn get_max_decibel(&mut self) -> Result<u8, Error<E>> {
+        self.read_byte(REG_MAX).await
+    }
+
+    /// Gets the min SPL value in decibels from the MIN register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn get_min_decibel(&mut self) -> Result<u8, Error<E>> {
+        self.read_byte(REG_MIN).await
+    }
+
+    /// Gets the audio spectrum from registers FREQ_64BINS_0 to FREQ_64BINS_63
+    /// in a single burst read.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Unsupported`] if the variant has no spectrum analyzer.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn read_spectrum(&mut self) -> Result<Spectrum, Error<E>> {
+        self.require(Variant::has_spectrum).await?;
+
+        let mut buffer: [u8; SPECTRUM_BINS] = [0; SPECTRUM_BINS];
+        self.read_bytes(REG_FREQ_64BINS_0, &mut buffer).await?;
+
+        Ok(Spectrum::from_bins(buffer))
+    }
+
+    /// Gets the value stored in the SCRATCH register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn get_scratch(&mut self) -> Result<u8, Error<E>> {
+        self.read_byte(REG_SCRATCH).await
+    }
+
+    /// Gets the lower interrupt threshold in decibels from the THR_MIN register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn get_threshold_min(&mut self) -> Result<u8, Error<E>> {
+        self.read_byte(REG_THR_MIN).await
+    }
+
+    /// Gets the upper interrupt threshold in decibels from the THR_MAX register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn get_threshold_max(&mut self) -> Result<u8, Error<E>> {
+        self.read_byte(REG_THR_MAX).await
+    }
+
+    /// Gets the interrupt threshold window in decibels as `(min, max)` from
+    /// the THR_MIN and THR_MAX registers.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn get_threshold_window(&mut self) -> Result<(u8, u8), Error<E>> {
+        let mut buffer: [u8; 2] = [0; 2];
+        self.read_bytes(REG_THR_MIN, &mut buffer).await?;
+        Ok((buffer[0], buffer[1]))
+    }
+
+    /// Soft resets the sensor.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn reset(&mut self) -> Result<(), Error<E>> {
+        self.set_reset_register(ResetRegister::new().with_system_reset(true))
+            .await
+    }
+
+    /// Sets the averaging time for calculating SPL.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn set_avg_time(&mut self, averaging_time: AveragingTime) -> Result<(), Error<E>> {
+        self.write_two_bytes(REG_TAVG_HIGH, &averaging_time.ms().to_be_bytes())
+            .await
+    }
+
+    /// Sets the CONTROL register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Unsupported`] if the Enable Line Output bit is set and
+    /// the variant has no line output.
+    ///
+    /// Returns [`Error::ReservedBits`] if `reg` has reserved bits set or holds
+    /// the reserved filter setting.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn set_control_register(&mut self, reg: ControlRegister) -> Result<(), Error<E>> {
+        if reg.line_out() {
+            self.require(Variant::has_line_out).await?;
+        }
+
+        self.write_byte(REG_CONTROL, reg.into_bits()).await
+    }
+
+    /// Sets the microphone gain in the GAIN register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Unsupported`] if the variant has no adjustable gain.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn set_gain(&mut self, gain: Gain) -> Result<(), Error<E>> {
+        self.require(Variant::has_gain).await?;
+        self.write_byte(REG_GAIN, gain.steps()).await
+    }
+
+    /// Enables or disables the line output with a read-modify-write of the
+    /// CONTROL register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Unsupported`] if the variant has no line output.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn set_line_out(&mut self, enable: bool) -> Result<(), Error<E>> {
+        self.require(Variant::has_line_out).await?;
+        let mut reg_control = self.get_control_register().await?;
+        reg_control.set_line_out(enable);
+        self.write_byte(REG_CONTROL, reg_control.into_bits()).await
+    }
+
+    /// Sets the lower interrupt threshold in decibels in the THR_MIN register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn set_threshold_min(&mut self, db: u8) -> Result<(), Error<E>> {
+        self.write_byte(REG_THR_MIN, db).await
+    }
+
+    /// Sets the upper interrupt threshold in decibels in the THR_MAX register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn set_threshold_max(&mut self, db: u8) -> Result<(), Error<E>> {
+        self.write_byte(REG_THR_MAX, db).await
+    }
+
+    /// Sets the interrupt threshold window in decibels in the THR_MIN and
+    /// THR_MAX registers with a single write.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidArgument`] if `min` is greater than `max`.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn set_threshold_window(&mut self, min: u8, max: u8) -> Result<(), Error<E>> {
+        if min > max {
+            return Err(Error::InvalidArgument);
+        }
+
+        self.write_two_bytes(REG_THR_MIN, &[min, max]).await
+    }
+
+    /// Enables the threshold interrupt with a read-modify-write of the
+    /// CONTROL register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn enable_threshold_interrupt(&mut self) -> Result<(), Error<E>> {
+        let mut reg_control = self.get_control_register().await?;
+        reg_control.set_threshold_interrupt(true);
+        self.set_control_register(reg_control).await
+    }
+
+    /// Disables the threshold interrupt with a read-modify-write of the
+    /// CONTROL register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn disable_threshold_interrupt(&mut self) -> Result<(), Error<E>> {
+        let mut reg_control = self.get_control_register().await?;
+        reg_control.set_threshold_interrupt(false);
+        self.set_control_register(reg_control).await
+    }
+
+    /// Sets the RESET register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn set_reset_register(&mut self, reg: ResetRegister) -> Result<(), Error<E>> {
+        self.write_byte(REG_RESET, reg.into_bits()).await
+    }
+
+    /// Sets the value stored in the SCRATCH register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn set_scratch(&mut self, value: u8) -> Result<(), Error<E>> {
+        self.write_byte(REG_SCRATCH, value).await
+    }
+
+    /// Applies the configuration `config` to the module.
+    ///
+    /// See [`PaSpl::apply`](crate::PaSpl::apply).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidArgument`] if the minimum threshold is greater
+    /// than the maximum threshold.
+    ///
+    /// Returns [`Error::Unsupported`] if `config` sets the gain or enables the
+    /// line output and the variant does not support it.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn apply(&mut self, config: &Config) -> Result<(), Error<E>> {
+        let (min, max) = config.thresholds;
+        if min > max {
+            return Err(Error::InvalidArgument);
+        }
+        let variant = self.get_variant().await?;
+        if (config.gain.is_some() && !variant.has_gain())
+            || (config.line_out && !variant.has_line_out())
+        {
+            return Err(Error::Unsupported(variant));
+        }
+
+        let (live, invalid) = self.read_live_config().await?;
+        let diff = config.diff(&live).rewriting(invalid, config);
+        self.write_config(config, &diff).await
+    }
+
+    /// Reads the live configuration of the module.
+    ///
+    /// The gain is only read on modules with adjustable gain.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidRegisterValue`] if TAVG or GAIN holds a value
+    /// that is not a valid averaging time or gain.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn read_config(&mut self) -> Result<Config, Error<E>> {
+        let control = self.get_control_register().await?;
+        let averaging_time = self.get_avg_time().await?;
+        let thresholds = self.get_threshold_window().await?;
+        let gain = if self.get_variant().await?.has_gain() {
+            Some(self.get_gain().await?)
+        } else {
+            None
+        };
+
+        Ok(Config {
+            filter: control.filter(),
+            averaging_time,
+            interrupt: control.interrupt_mode(),
+            thresholds,
+            gain,
+            line_out: control.line_out(),
+        })
+    }
+
+    /// Runs a self-test of the I2C communication with the module.
+    ///
+    /// See [`PaSpl::self_test`](crate::PaSpl::self_test).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error. The original SCRATCH
+    /// value is still restored if possible.
+    ///
+    pub async fn self_test(&mut self) -> Result<SelfTestReport, Error<E>> {
+        let original = self.get_scratch().await?;
+
+        let patterns = self.check_scratch_patterns().await;
+        let restored = match self.set_scratch(original).await {
+            Ok(()) => self.get_scratch().await,
+            Err(e) => Err(e),
+        };
+        let (walking_ones, walking_zeros, alternating) = patterns?;
+        let scratch_restored = restored? == original;
+
+        let version = self.get_version().await?.variant() != Variant::Unknown;
+        let control_reserved_bits = self.read_byte(REG_CONTROL).await? & CONTROL_RESERVED_MASK == 0;
+
+        Ok(SelfTestReport {
+            walking_ones,
+            walking_zeros,
+            alternating,
+            scratch_restored,
+            version,
+            control_reserved_bits,
+        })
+    }
+
+    /// Destroys this driver and releases the I2C bus.
+    ///
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    /// Checks that the variant of the module supports a feature.
+    ///
+    /// Writes each group of test patterns to SCRATCH and reads them back.
+    ///
+    async fn check_scratch_patterns(&mut self) -> Result<(bool, bool, bool), Error<E>> {
+        let mut walking_ones = true;
+        let mut walking_zeros = true;
+        for bit in 0..8 {
+            walking_ones &= self.check_scratch_pattern(1 << bit).await?;
+            walking_zeros &= self.check_scratch_pattern(!(1 << bit)).await?;
+        }
+
+        let mut alternating = true;
+        for pattern in ALTERNATING_PATTERNS {
+            alternating &= self.check_scratch_pattern(pattern).await?;
+        }
+
+        Ok((walking_ones, walking_zeros, alternating))
+    }
+
+    /// Writes `pattern` to SCRATCH and returns true if it reads back
+    /// unchanged.
+    ///
+    async fn check_scratch_pattern(&mut self, pattern: u8) -> Result<bool, Error<E>> {
+        self.write_byte(REG_SCRATCH, pattern).await?;
+        Ok(self.read_byte(REG_SCRATCH).await? == pattern)
+    }
+
+    async fn require(&mut self, supported: fn(Variant) -> bool) -> Result<(), Error<E>> {
+        let variant = self.get_variant().await?;
+        if supported(variant) {
+            Ok(())
+        } else {
+            Err(Error::Unsupported(variant))
+        }
+    }
+
+    /// Reads the live configuration like [`PaSplAsync::read_config`], but does
+    /// not fail on a TAVG or GAIN register holding an invalid value.
+    ///
+    /// See [`PaSpl::read_live_config`](crate::PaSpl::read_live_config).
+    ///
+    async fn read_live_config(&mut self) -> Result<(Config, ConfigDiff), Error<E>> {
+        let control = self.get_control_register().await?;
+        let averaging_time = invalid_as_none(self.get_avg_time().await)?;
+        let thresholds = self.get_threshold_window().await?;
+        let gain = if self.get_variant().await?.has_gain() {
+            invalid_as_none(self.get_gain().await)?.map(Some)
+        } else {
+            Some(None)
+        };
+
+        let config = Config {
+            filter: control.filter(),
+            averaging_time: averaging_time.unwrap_or(AveragingTime::DEFAULT),
+            interrupt: control.interrupt_mode(),
+            thresholds,
+            gain: gain.flatten(),
+            line_out: control.line_out(),
+        };
+        let invalid = ConfigDiff {
+            averaging_time: averaging_time.is_none(),
+            gain: gain.is_none(),
+            ..ConfigDiff::default()
+        };
+        Ok((config, invalid))
+    }
+
+    /// Writes the fields of `config` flagged in `diff` to the module.
+    ///
+    async fn write_config(&mut self, config: &Config, diff: &ConfigDiff) -> Result<(), Error<E>> {
+        if diff.averaging_time {
+            self.write_two_bytes(REG_TAVG_HIGH, &config.averaging_time.ms().to_be_bytes())
+                .await?;
+        }
+        if diff.thresholds {
+            let (min, max) = config.thresholds;
+            self.write_two_bytes(REG_THR_MIN, &[min, max]).await?;
+        }
+        if let (true, Some(gain)) = (diff.gain, config.gain) {
+            self.write_byte(REG_GAIN, gain.steps()).await?;
+        }
+        if diff.control() {
+            self.write_byte(REG_CONTROL, config.control_register().into_bits())
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Reads a single byte from an I2C register of the device.
+    ///
+    async fn read_byte(&mut self, reg: u8) -> Result<u8, Error<E>> {
+        debug_assert!(is_readable_range(reg, 1));
+        let mut buffer = [0; 1];
+        self.i2c
+            .write_read(self.device_addr, &[reg], &mut buffer)
+            .await
+            .map_err(Error::i2c(reg, Operation::Read))?;
+        Ok(buffer[0])
+    }
+
+    /// Reads multiple bytes from a starting register in a single transaction.
+    ///
+    async fn read_bytes(&mut self, start_reg: u8, buffer: &mut [u8]) -> Result<(), Error<E>> {
+        debug_assert!(is_readable_range(start_reg, buffer.len()));
+        let reg = [start_reg];
+        let mut operations = [i2c::Operation::Write(&reg), i2c::Operation::Read(buffer)];
+        self.i2c
+            .transaction(self.device_addr, &mut operations)
+            .await
+            .map_err(Error::i2c(start_reg, Operation::Read))
+    }
+
+    /// Writes a single byte to an I2C register of the device.
+    ///
+    async fn write_byte(&mut self, reg: u8, value: u8) -> Result<(), Error<E>> {
+        debug_assert!(is_writable_range(reg, 1));
+        if has_reserved_bits(reg, value) {
+            return Err(Error::ReservedBits {
+                register: reg,
+                value,
+            });
+        }
+        self.i2c
+            .write(self.device_addr, &[reg, value])
+            .await
+            .map_err(Error::i2c(reg, Operation::Write))?;
+        self.verify_write(reg, &[value]).await
+    }
+
+    /// Writes two bytes from a starting register.
+    ///
+    async fn write_two_bytes(&mut self, reg: u8, buffer: &[u8; 2]) -> Result<(), Error<E>> {
+        debug_assert!(is_writable_range(reg, 2));
+        self.i2c
+            .write(self.device_addr, &[reg, buffer[0], buffer[1]])
+            .await
+            .map_err(Error::i2c(reg, Operation::Write))?;
+        self.verify_write(reg, buffer).await
+    }
+
+    /// Reads back the bytes `written` from a starting register and compares
+    /// them, if writes are verified.
+    ///
+    async fn verify_write(&mut self, start_reg: u8, written: &[u8]) -> Result<(), Error<E>> {
+        if !self.verify_writes || verify_mask(start_reg).is_none() {
+            return Ok(());
+        }
+
+        let mut buffer = [0; 2];
+        let read = &mut buffer[..written.len()];
+        if let [value] = read {
+            *value = self.read_byte(start_reg).await?;
+        } else {
+            self.read_bytes(start_reg, read).await?;
+        }
+
+        compare_written(start_reg, written, read)
+    }
+}
+
+/// The open-drain INT pin of a PA SPL Module on the async input pin `P`.
+///
+/// The async twin of [`InterruptPin`](crate::InterruptPin).
+pub struct InterruptPinAsync<P>
+where
+    P: Wait,
+{
+    pin: P,
+}
+
+impl<P> InterruptPinAsync<P>
+where
+    P: Wait,
+{
+    /// Initializes the INT pin of the module on the input pin `pin`.
+    ///
+    pub fn new(pin: P) -> Self {
+        Self { pin }
+    }
+
+    /// Waits until an interrupt is pending, i.e. the INT pin is low.
+    ///
+    /// Returns immediately if the pin is already low.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the input pin if waiting on it fails.
+    ///
+    pub async fn wait_for_interrupt(&mut self) -> Result<(), P::Error> {
+        self.pin.wait_for_low().await
+    }
+
+    /// Reads the latest decibel reading, then acknowledges a pending
+    /// interrupt.
+    ///
+    /// Reads the DECIBEL register of `pa_spl`, then clears the interrupt,
+    /// which releases the INT pin.
+    ///
+    /// Returns the latest decibel reading, not the reading that caused the
+    /// interrupt: DECIBEL may have been updated one or more averaging periods
+    /// after the threshold was crossed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub async fn read_latest_and_acknowledge<E, I2C>(
+        &mut self,
+        pa_spl: &mut PaSplAsync<I2C>,
+    ) -> Result<u8, Error<E>>
+    where
+        I2C: I2c<Error = E>,
+    {
+        let decibel = pa_spl.get_latest_decibel().await?;
+        pa_spl.clear_interrupt().await?;
+        Ok(decibel)
+    }
+
+    /// Destroys this INT pin and releases the input pin.
+    ///
+    pub fn release(self) -> P {
+        self.pin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InterruptPinAsync, PaSplAsync};
+    use crate::{
+        AveragingTime, Config, ControlRegister, Error, FilterSetting, InterruptMode,
+        SelfTestReport, Variant, DEVICE_ADDR_DEFAULT, REG_CONTROL, REG_DBHISTORY_0, REG_DECIBEL,
+        REG_FREQ_64BINS_0, REG_RESET, REG_SCRATCH, REG_TAVG_HIGH, REG_THR_MAX, REG_THR_MIN,
+        REG_VERSION, SPECTRUM_BINS,
+    };
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+    use embedded_hal_mock::eh1::digital::{
+        Mock as PinMock, State as PinState, Transaction as PinTransaction,
+    };
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+    /// Runs a future to completion by polling it in a loop.
+    ///
+    /// The mocks never return `Poll::Pending`, so no executor is needed.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        const VTABLE: RawWakerVTable = RawWakerVTable::new(
+            |_| RawWaker::new(core::ptr::null(), &VTABLE),
+            |_| {},
+            |_| {},
+            |_| {},
+        );
+        let waker = unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &VTABLE)) };
+        let mut context = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    /// Expectations for reading consecutive registers in one transaction.
+    fn read_registers(start_reg: u8, data: Vec<u8>) -> Vec<I2cTransaction> {
+        vec![
+            I2cTransaction::transaction_start(DEVICE_ADDR_DEFAULT),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![start_reg]),
+            I2cTransaction::read(DEVICE_ADDR_DEFAULT, data),
+            I2cTransaction::transaction_end(DEVICE_ADDR_DEFAULT),
+        ]
+    }
+
+    #[test]
+    fn confirm_verify_writes() {
+        let mut expectations = vec![I2cTransaction::write(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_TAVG_HIGH, 0x00, 0x7D],
+        )];
+        expectations.extend(read_registers(REG_TAVG_HIGH, vec![0x00, 0x7D]));
+        expectations.extend([
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0b0000_0100]),
+            // The reserved bits of CONTROL are not compared.
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL], vec![0b1100_0100]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_THR_MIN, 40, 95]),
+        ]);
+        expectations.extend(read_registers(REG_THR_MIN, vec![40, 90]));
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSplAsync::new(i2c_mock);
+        pa_spl.set_verify_writes(true);
+        assert!(pa_spl.verify_writes());
+
+        let mut reg_control = ControlRegister::new();
+        reg_control.set_filter(FilterSetting::CWeighting);
+        block_on(async {
+            assert_eq!(Ok(()), pa_spl.set_avg_time(AveragingTime::FAST).await);
+            assert_eq!(Ok(()), pa_spl.set_control_register(reg_control).await);
+            assert_eq!(
+                Err(Error::VerifyFailed {
+                    register: REG_THR_MAX,
+                    written: 95,
+                    read: 90,
+                }),
+                pa_spl.set_threshold_window(40, 95).await
+            );
+        });
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_apply_and_read_config() {
+        let mut expectations = vec![
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_VERSION], vec![0x32]),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL], vec![0x02]),
+        ];
+        expectations.extend(read_registers(REG_TAVG_HIGH, vec![0x03, 0xE8]));
+        expectations.extend(read_registers(REG_THR_MIN, vec![45, 85]));
+        expectations.extend([
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_THR_MIN, 40, 95]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0b0001_1010]),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL], vec![0b0001_1010]),
+        ]);
+        expectations.extend(read_registers(REG_TAVG_HIGH, vec![0x03, 0xE8]));
+        expectations.extend(read_registers(REG_THR_MIN, vec![40, 95]));
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSplAsync::new(i2c_mock);
+
+        let config = Config {
+            interrupt: InterruptMode::Threshold,
+            thresholds: (40, 95),
+            ..Config::default()
+        };
+        block_on(async {
+            pa_spl.apply(&config).await.unwrap();
+            assert_eq!(Ok(config), pa_spl.read_config().await);
+        });
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_apply_rewrites_invalid_avg_time() {
+        let mut expectations = vec![
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_VERSION], vec![0x32]),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL], vec![0x02]),
+        ];
+        expectations.extend(read_registers(REG_TAVG_HIGH, vec![0x00, 0x00]));
+        expectations.extend(read_registers(REG_THR_MIN, vec![45, 85]));
+        expectations.push(I2cTransaction::write(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_TAVG_HIGH, 0x03, 0xE8],
+        ));
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSplAsync::new(i2c_mock);
+
+        block_on(pa_spl.apply(&Config::default())).unwrap();
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_self_test() {
+        let mut expectations = vec![I2cTransaction::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_SCRATCH],
+            vec![0x3c],
+        )];
+        let mut patterns = vec![];
+        for bit in 0..8 {
+            patterns.extend([1 << bit, !(1 << bit)]);
+        }
+        patterns.extend([0x55, 0xaa, 0x3c]);
+        for pattern in patterns {
+            expectations.extend([
+                I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_SCRATCH, pattern]),
+                I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_SCRATCH], vec![pattern]),
+            ]);
+        }
+        expectations.extend([
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_VERSION], vec![0x32]),
+            // Reserved bit 7 of CONTROL is set.
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL], vec![0x82]),
+        ]);
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSplAsync::new(i2c_mock);
+
+        let report = block_on(pa_spl.self_test()).unwrap();
+        assert_eq!(
+            SelfTestReport {
+                walking_ones: true,
+                walking_zeros: true,
+                alternating: true,
+                scratch_restored: true,
+                version: true,
+                control_reserved_bits: false,
+            },
+            report
+        );
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_register_access() {
+        let mut expectations = vec![
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_DECIBEL], vec![72]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_TAVG_HIGH, 0x00, 0x7D]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_THR_MIN, 40, 95]),
+        ];
+        expectations.extend(read_registers(REG_TAVG_HIGH, vec![0x00, 0x7D]));
+        expectations.extend([
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL], vec![0x02]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0b0001_1010]),
+        ]);
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSplAsync::new(i2c_mock);
+
+        block_on(async {
+            assert_eq!(Ok(72), pa_spl.get_latest_decibel().await);
+            pa_spl.set_avg_time(AveragingTime::FAST).await.unwrap();
+            pa_spl.set_threshold_window(40, 95).await.unwrap();
+            assert_eq!(Ok(AveragingTime::FAST), pa_spl.get_avg_time().await);
+            pa_spl.enable_threshold_interrupt().await.unwrap();
+            assert_eq!(
+                Err(Error::InvalidArgument),
+                pa_spl.set_threshold_window(95, 40).await
+            );
+        });
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_read_history_and_spectrum() {
+        let history: Vec<u8> = (0..10).map(|i| 50 + i).collect();
+        let bins: Vec<u8> = (0..SPECTRUM_BINS as u8).collect();
+        let mut expectations = read_registers(REG_DBHISTORY_0, history.clone());
+        expectations.push(I2cTransaction::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_VERSION],
+            vec![0x32],
+        ));
+        expectations.extend(read_registers(REG_FREQ_64BINS_0, bins.clone()));
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSplAsync::new(i2c_mock);
+
+        block_on(async {
+            let result = pa_spl.read_history_range(0, 10).await.unwrap();
+            assert_eq!(history.as_slice(), result.as_slice());
+            let spectrum = pa_spl.read_spectrum().await.unwrap();
+            assert_eq!(bins.as_slice(), spectrum.bins());
+            // The version is cached, so the variant is known without a read.
+            assert_eq!(
+                Ok(Variant::MemsSpectrumAnalyzer),
+                pa_spl.get_variant().await
+            );
+        });
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_wait_for_interrupt_and_read_latest_and_acknowledge() {
+        let pin_expectations = [PinTransaction::wait_for_state(PinState::Low)];
+        let expectations = vec![
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_DECIBEL], vec![97]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_RESET, 0b0000_0001]),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSplAsync::new(i2c_mock);
+        let mut int_pin = InterruptPinAsync::new(PinMock::new(&pin_expectations));
+
+        block_on(async {
+            int_pin.wait_for_interrupt().await.unwrap();
+            assert_eq!(
+                Ok(97),
+                int_pin.read_latest_and_acknowledge(&mut pa_spl).await
+            );
+        });
+
+        let mut mock = pa_spl.release();
+        mock.done();
+        let mut pin = int_pin.release();
+        pin.done();
+    }
+}
@@ -0,0 +1,163 @@
+//! Averaging time of the TAVG registers.
+
+use core::time::Duration;
+
+use defmt::Format;
+
+use crate::REG_AVERAGING_TIME_DEFAULT_MS;
+
+/// Time window over which the decibel value is averaged.
+///
+/// The averaging time is set in ms from 10 ms to 10,000 ms. An
+/// `AveragingTime` can only hold a value in that range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Format)]
+pub struct AveragingTime(u16);
+
+impl AveragingTime {
+    /// Minimum averaging time in ms.
+    pub const MIN_MS: u16 = 10;
+    /// Maximum averaging time in ms.
+    pub const MAX_MS: u16 = 10_000;
+    /// Minimum averaging time, 10 ms.
+    pub const MIN: Self = Self(Self::MIN_MS);
+    /// Maximum averaging time, 10,000 ms.
+    pub const MAX: Self = Self(Self::MAX_MS);
+    /// Fast mode of sound level meters, 125 ms.
+    pub const FAST: Self = Self(125);
+    /// Slow mode of sound level meters, 1000 ms.
+    pub const SLOW: Self = Self(1000);
+    /// Power-up default averaging time, 1000 ms (slow mode).
+    pub const DEFAULT: Self = Self(REG_AVERAGING_TIME_DEFAULT_MS);
+
+    /// Creates an averaging time from a value in ms.
+    ///
+    /// Returns `None` if `ms` is less than [`AveragingTime::MIN_MS`] or
+    /// greater than [`AveragingTime::MAX_MS`].
+    ///
+    pub const fn from_ms(ms: u16) -> Option<Self> {
+        if ms >= Self::MIN_MS && ms <= Self::MAX_MS {
+            Some(Self(ms))
+        } else {
+            None
+        }
+    }
+
+    /// Creates an averaging time from a duration.
+    ///
+    /// Returns `None` if `duration` is not a whole number of ms from 10 ms to
+    /// 10,000 ms.
+    ///
+    pub fn from_duration(duration: Duration) -> Option<Self> {
+        if duration.subsec_nanos() % 1_000_000 != 0 {
+            return None;
+        }
+        let ms = u16::try_from(duration.as_millis()).ok()?;
+        Self::from_ms(ms)
+    }
+
+    /// Gets the averaging time in ms, the value of the TAVG registers.
+    ///
+    pub const fn ms(self) -> u16 {
+        self.0
+    }
+
+    /// Gets the averaging time as a duration.
+    ///
+    pub const fn as_duration(self) -> Duration {
+        Duration::from_millis(self.0 as u64)
+    }
+}
+
+impl Default for AveragingTime {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl TryFrom<u16> for AveragingTime {
+    type Error = u16;
+
+    /// Creates an averaging time from a value in ms, returning the rejected
+    /// value if it is out of range.
+    fn try_from(ms: u16) -> Result<Self, Self::Error> {
+        Self::from_ms(ms).ok_or(ms)
+    }
+}
+
+impl TryFrom<Duration> for AveragingTime {
+    type Error = Duration;
+
+    /// Creates an averaging time from a duration, returning the rejected
+    /// duration if it is out of range or not a whole number of ms.
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        Self::from_duration(duration).ok_or(duration)
+    }
+}
+
+impl From<AveragingTime> for u16 {
+    fn from(averaging_time: AveragingTime) -> Self {
+        averaging_time.ms()
+    }
+}
+
+impl From<AveragingTime> for Duration {
+    fn from(averaging_time: AveragingTime) -> Self {
+        averaging_time.as_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AveragingTime;
+    use core::time::Duration;
+
+    #[test]
+    fn confirm_from_ms() {
+        assert_eq!(Some(AveragingTime::MIN), AveragingTime::from_ms(10));
+        assert_eq!(Some(AveragingTime::FAST), AveragingTime::from_ms(125));
+        assert_eq!(Some(AveragingTime::SLOW), AveragingTime::from_ms(1000));
+        assert_eq!(Some(AveragingTime::MAX), AveragingTime::from_ms(10_000));
+        assert_eq!(None, AveragingTime::from_ms(0));
+        assert_eq!(None, AveragingTime::from_ms(9));
+        assert_eq!(None, AveragingTime::from_ms(10_001));
+        assert_eq!(None, AveragingTime::from_ms(u16::MAX));
+        assert_eq!(Err(0), AveragingTime::try_from(0));
+        assert_eq!(AveragingTime::DEFAULT, AveragingTime::default());
+        assert_eq!(AveragingTime::SLOW, AveragingTime::DEFAULT);
+    }
+
+    #[test]
+    fn confirm_from_duration() {
+        assert_eq!(
+            Some(AveragingTime::FAST),
+            AveragingTime::from_duration(Duration::from_millis(125))
+        );
+        assert_eq!(
+            Some(AveragingTime::MAX),
+            AveragingTime::from_duration(Duration::from_secs(10))
+        );
+        assert_eq!(None, AveragingTime::from_duration(Duration::ZERO));
+        assert_eq!(None, AveragingTime::from_duration(Duration::from_secs(11)));
+        assert_eq!(
+            None,
+            AveragingTime::from_duration(Duration::from_micros(125_500))
+        );
+        assert_eq!(None, AveragingTime::from_duration(Duration::MAX));
+        assert_eq!(
+            Err(Duration::from_millis(5)),
+            AveragingTime::try_from(Duration::from_millis(5))
+        );
+    }
+
+    #[test]
+    fn confirm_round_trip() {
+        for ms in AveragingTime::MIN_MS..=AveragingTime::MAX_MS {
+            let averaging_time = AveragingTime::from_ms(ms).unwrap();
+            assert_eq!(
+                Some(averaging_time),
+                AveragingTime::from_duration(averaging_time.as_duration())
+            );
+            assert_eq!(ms, u16::from(averaging_time));
+        }
+    }
+}
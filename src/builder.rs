@@ -0,0 +1,344 @@
+//! Builder for a driver that is ready to use.
+
+use crate::hal::{Delay, I2cBus, InputPin};
+use crate::{verify_scratch, Config, Error, InterruptPin, PaSpl, Variant, DEVICE_ADDR_DEFAULT};
+
+/// Time in ms for the decibel reading to stabilize after power-up.
+pub const WARM_UP_DELAY_MS: u32 = 1000;
+
+/// Placeholder for the INT pin of a [`PaSplBuilder`] without one.
+pub struct NoPin;
+
+/// Builder of a [`PaSpl`] driver on the I2C bus `I2C`, with the INT pin `P`.
+///
+/// Collects the address, expected variant, initial configuration and INT pin
+/// of the module, then [`build`](PaSplBuilder::build) or
+/// [`build_with_interrupt`](PaSplBuilder::build_with_interrupt) runs the
+/// startup sequence and returns a driver that is ready to use.
+pub struct PaSplBuilder<I2C, P = NoPin>
+where
+    I2C: I2cBus,
+{
+    i2c: I2C,
+    address: u8,
+    variant: Option<Variant>,
+    config: Option<Config>,
+    verify_writes: bool,
+    pin: P,
+}
+
+impl<I2C> PaSplBuilder<I2C>
+where
+    I2C: I2cBus,
+{
+    /// Initializes a builder of a driver on the I2C bus `i2c`.
+    ///
+    /// The address defaults to the published address 0x48, any variant is
+    /// accepted, the configuration is left as it is, and there is no INT pin.
+    ///
+    pub fn new(i2c: I2C) -> Self {
+        Self {
+            i2c,
+            address: DEVICE_ADDR_DEFAULT,
+            variant: None,
+            config: None,
+            verify_writes: false,
+            pin: NoPin,
+        }
+    }
+
+    /// Sets the input pin connected to the INT pin of the module.
+    ///
+    /// Build the driver with [`PaSplBuilder::build_with_interrupt`] to also get
+    /// an [`InterruptPin`].
+    ///
+    pub fn interrupt_pin<P>(self, pin: P) -> PaSplBuilder<I2C, P>
+    where
+        P: InputPin,
+    {
+        PaSplBuilder {
+            i2c: self.i2c,
+            address: self.address,
+            variant: self.variant,
+            config: self.config,
+            verify_writes: self.verify_writes,
+            pin,
+        }
+    }
+
+    /// Builds the driver.
+    ///
+    /// Verifies that the module is present with the SCRATCH write-read-verify
+    /// check recommended by the manual and that it is the expected variant,
+    /// waits [`WARM_UP_DELAY_MS`] for the decibel reading to stabilize, then
+    /// applies the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotPresent`] if the device at the address fails the
+    /// SCRATCH check.
+    ///
+    /// Returns [`Error::VariantMismatch`] if the module is not the expected
+    /// variant.
+    ///
+    /// Returns the errors of [`PaSpl::apply`] if applying the configuration
+    /// fails.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    /// The I2C bus is returned along with the error, so it can be used to
+    /// retry or for other devices.
+    ///
+    pub fn build<E, D>(self, delay: &mut D) -> Result<PaSpl<I2C>, (I2C, Error<E>)>
+    where
+        I2C: I2cBus<Error = E>,
+        D: Delay,
+    {
+        self.start(delay)
+            .map(|(pa_spl, NoPin)| pa_spl)
+            .map_err(|(i2c, NoPin, e)| (i2c, e))
+    }
+}
+
+impl<I2C, P> PaSplBuilder<I2C, P>
+where
+    I2C: I2cBus,
+{
+    /// Sets the I2C address of the module.
+    ///
+    pub fn address(mut self, address: u8) -> Self {
+        self.address = address;
+        self
+    }
+
+    /// Sets the variant the module is expected to be.
+    ///
+    pub fn variant(mut self, variant: Variant) -> Self {
+        self.variant = Some(variant);
+        self
+    }
+
+    /// Sets the configuration applied to the module.
+    ///
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Enables or disables verification of configuration writes, including
+    /// those applying the configuration.
+    ///
+    /// See [`PaSpl::set_verify_writes`].
+    ///
+    pub fn verify_writes(mut self, enable: bool) -> Self {
+        self.verify_writes = enable;
+        self
+    }
+
+    /// Runs the startup sequence and returns the driver with the INT pin, or
+    /// the I2C bus and the INT pin with the error.
+    ///
+    #[allow(clippy::type_complexity)]
+    fn start<E, D>(self, delay: &mut D) -> Result<(PaSpl<I2C>, P), (I2C, P, Error<E>)>
+    where
+        I2C: I2cBus<Error = E>,
+        D: Delay,
+    {
+        let mut pa_spl = PaSpl::new(self.i2c);
+        pa_spl.set_device_addr(self.address);
+        pa_spl.set_verify_writes(self.verify_writes);
+
+        match startup(&mut pa_spl, self.variant, self.config.as_ref(), delay) {
+            Ok(()) => Ok((pa_spl, self.pin)),
+            Err(e) => Err((pa_spl.release(), self.pin, e)),
+        }
+    }
+}
+
+impl<I2C, P> PaSplBuilder<I2C, P>
+where
+    I2C: I2cBus,
+    P: InputPin,
+{
+    /// Builds the driver and returns it with its [`InterruptPin`].
+    ///
+    /// Runs the same startup sequence as [`PaSplBuilder::build`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`PaSplBuilder::build`], along with the I2C
+    /// bus and the input pin.
+    ///
+    #[allow(clippy::type_complexity)]
+    pub fn build_with_interrupt<E, D>(
+        self,
+        delay: &mut D,
+    ) -> Result<(PaSpl<I2C>, InterruptPin<P>), (I2C, P, Error<E>)>
+    where
+        I2C: I2cBus<Error = E>,
+        D: Delay,
+    {
+        self.start(delay)
+            .map(|(pa_spl, pin)| (pa_spl, InterruptPin::new(pin)))
+    }
+}
+
+/// Verifies the module is present and the expected variant, waits for the
+/// decibel reading to stabilize, then applies the configuration.
+///
+fn startup<E, I2C, D>(
+    pa_spl: &mut PaSpl<I2C>,
+    variant: Option<Variant>,
+    config: Option<&Config>,
+    delay: &mut D,
+) -> Result<(), Error<E>>
+where
+    I2C: I2cBus<Error = E>,
+    D: Delay,
+{
+    if !verify_scratch(&mut pa_spl.i2c, pa_spl.device_addr)? {
+        return Err(Error::NotPresent);
+    }
+    let found = pa_spl.get_variant()?;
+    if let Some(expected) = variant {
+        if found != expected {
+            return Err(Error::VariantMismatch { expected, found });
+        }
+    }
+
+    delay.delay_ms(WARM_UP_DELAY_MS);
+
+    if let Some(config) = config {
+        pa_spl.apply(config)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PaSplBuilder, WARM_UP_DELAY_MS};
+    use crate::hal::Delay;
+    use crate::mock::{read_registers, I2cMock, I2cTransaction, PinMock};
+    use crate::{
+        AveragingTime, Config, Error, Variant, REG_CONTROL, REG_CONTROL_DEFAULT, REG_SCRATCH,
+        REG_TAVG_HIGH, REG_THR_MIN, REG_VERSION,
+    };
+
+    const ADDR: u8 = 0x49;
+
+    /// Delay that records the total time waited.
+    #[derive(Default)]
+    struct RecordingDelay {
+        total_ms: u32,
+    }
+
+    impl Delay for RecordingDelay {
+        fn delay_ms(&mut self, ms: u32) {
+            self.total_ms += ms;
+        }
+    }
+
+    /// Expectations for the SCRATCH check, with `read_back` read after
+    /// writing the complement of 0xAA.
+    fn scratch_expectations(read_back: u8) -> Vec<I2cTransaction> {
+        vec![
+            I2cTransaction::write_read(ADDR, vec![REG_SCRATCH], vec![0xaa]),
+            I2cTransaction::write(ADDR, vec![REG_SCRATCH, 0x55]),
+            I2cTransaction::write_read(ADDR, vec![REG_SCRATCH], vec![read_back]),
+            I2cTransaction::write(ADDR, vec![REG_SCRATCH, 0xaa]),
+        ]
+    }
+
+    #[test]
+    fn confirm_build() {
+        let mut expectations = scratch_expectations(0x55);
+        expectations.extend([
+            I2cTransaction::write_read(ADDR, vec![REG_VERSION], vec![0x32]),
+            I2cTransaction::write_read(ADDR, vec![REG_CONTROL], vec![REG_CONTROL_DEFAULT]),
+        ]);
+        expectations.extend(read_registers(ADDR, REG_TAVG_HIGH, vec![0x03, 0xE8]));
+        expectations.extend(read_registers(ADDR, REG_THR_MIN, vec![45, 85]));
+        expectations.push(I2cTransaction::write(ADDR, vec![REG_TAVG_HIGH, 0x00, 0x7D]));
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut delay = RecordingDelay::default();
+
+        let pa_spl = PaSplBuilder::new(i2c_mock)
+            .address(ADDR)
+            .variant(Variant::MemsSpectrumAnalyzer)
+            .config(Config {
+                averaging_time: AveragingTime::FAST,
+                ..Config::default()
+            })
+            .build(&mut delay)
+            .unwrap();
+        assert_eq!(WARM_UP_DELAY_MS, delay.total_ms);
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_build_with_interrupt() {
+        let mut expectations = scratch_expectations(0x55);
+        expectations.push(I2cTransaction::write_read(
+            ADDR,
+            vec![REG_VERSION],
+            vec![0x81],
+        ));
+        let i2c_mock = I2cMock::new(&expectations);
+
+        let (pa_spl, int_pin) = PaSplBuilder::new(i2c_mock)
+            .address(ADDR)
+            .interrupt_pin(PinMock::new(&[]))
+            .build_with_interrupt(&mut RecordingDelay::default())
+            .unwrap();
+
+        let mut mock = pa_spl.release();
+        mock.done();
+        let mut pin = int_pin.release();
+        pin.done();
+    }
+
+    #[test]
+    fn confirm_build_not_present() {
+        let i2c_mock = I2cMock::new(&scratch_expectations(0xaa));
+        let mut delay = RecordingDelay::default();
+
+        let (mut mock, error) = PaSplBuilder::new(i2c_mock)
+            .address(ADDR)
+            .build(&mut delay)
+            .err()
+            .unwrap();
+        assert_eq!(Error::NotPresent, error);
+        assert_eq!(0, delay.total_ms);
+
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_build_variant_mismatch() {
+        let mut expectations = scratch_expectations(0x55);
+        expectations.push(I2cTransaction::write_read(
+            ADDR,
+            vec![REG_VERSION],
+            vec![0x31],
+        ));
+        let i2c_mock = I2cMock::new(&expectations);
+
+        let (mut mock, error) = PaSplBuilder::new(i2c_mock)
+            .address(ADDR)
+            .variant(Variant::ExternalMic)
+            .build(&mut RecordingDelay::default())
+            .err()
+            .unwrap();
+        assert_eq!(
+            Error::VariantMismatch {
+                expected: Variant::ExternalMic,
+                found: Variant::MemsLts,
+            },
+            error
+        );
+
+        mock.done();
+    }
+}
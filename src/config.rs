@@ -0,0 +1,509 @@
+//! Declarative configuration of the module.
+
+use defmt::Format;
+
+use crate::hal::I2cBus;
+use crate::{
+    AveragingTime, ControlRegister, Error, FilterSetting, Gain, PaSpl, REG_CONTROL,
+    REG_CONTROL_DEFAULT, REG_GAIN, REG_TAVG_HIGH, REG_THR_MAX_DEFAULT, REG_THR_MIN,
+    REG_THR_MIN_DEFAULT,
+};
+
+/// Source of the interrupt on the INT pin, set by the Interrupt Enable and
+/// Interrupt Type bits of the CONTROL register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Format)]
+pub enum InterruptMode {
+    /// The INT pin is not used.
+    Disabled,
+    /// The INT pin goes low when 90 of the 100 DBHISTORY registers are filled
+    /// with readings.
+    History,
+    /// The INT pin goes low when a reading falls outside the window set by
+    /// THR_MIN and THR_MAX.
+    Threshold,
+}
+
+/// Configuration of a PA SPL Module.
+///
+/// The default is the power-up configuration of the module. Apply a
+/// configuration with [`PaSpl::apply`] and read the live configuration with
+/// [`PaSpl::read_config`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Format)]
+pub struct Config {
+    /// Frequency weighting filter.
+    pub filter: FilterSetting,
+    /// Averaging time (TAVG).
+    pub averaging_time: AveragingTime,
+    /// Source of the interrupt on the INT pin.
+    pub interrupt: InterruptMode,
+    /// Interrupt threshold window in decibels as `(min, max)` (THR_MIN and
+    /// THR_MAX).
+    pub thresholds: (u8, u8),
+    /// Microphone gain, only available on modules with an external
+    /// microphone.
+    ///
+    /// `None` leaves the GAIN register unchanged, and is read back on modules
+    /// without adjustable gain.
+    pub gain: Option<Gain>,
+    /// Line output, only available on modules with an external microphone.
+    pub line_out: bool,
+}
+
+impl Config {
+    /// Gets the CONTROL register value for this configuration.
+    ///
+    pub fn control_register(&self) -> ControlRegister {
+        let mut reg = ControlRegister::new();
+        reg.set_filter(self.filter);
+        reg.set_interrupt_mode(self.interrupt);
+        reg.set_line_out(self.line_out);
+        reg
+    }
+
+    /// Compares this configuration with the configuration `other`, e.g. this
+    /// desired configuration with the live configuration of a module.
+    ///
+    /// The gain is only compared if it is set in this configuration.
+    ///
+    pub fn diff(&self, other: &Config) -> ConfigDiff {
+        ConfigDiff {
+            filter: self.filter != other.filter,
+            averaging_time: self.averaging_time != other.averaging_time,
+            interrupt: self.interrupt != other.interrupt,
+            thresholds: self.thresholds != other.thresholds,
+            gain: self.gain.is_some() && self.gain != other.gain,
+            line_out: self.line_out != other.line_out,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let control = ControlRegister::from_bits(REG_CONTROL_DEFAULT);
+        Self {
+            filter: control.filter(),
+            averaging_time: AveragingTime::DEFAULT,
+            interrupt: control.interrupt_mode(),
+            thresholds: (REG_THR_MIN_DEFAULT, REG_THR_MAX_DEFAULT),
+            gain: None,
+            line_out: control.line_out(),
+        }
+    }
+}
+
+/// Fields of two [`Config`]s that disagree, returned by [`Config::diff`].
+///
+/// A field is true if the configurations disagree on it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Format)]
+pub struct ConfigDiff {
+    /// The filters disagree.
+    pub filter: bool,
+    /// The averaging times disagree.
+    pub averaging_time: bool,
+    /// The interrupt modes disagree.
+    pub interrupt: bool,
+    /// The threshold windows disagree.
+    pub thresholds: bool,
+    /// The gains disagree.
+    pub gain: bool,
+    /// The line output settings disagree.
+    pub line_out: bool,
+}
+
+impl ConfigDiff {
+    /// Every field disagrees, used to write a configuration in full.
+    pub(crate) const ALL: Self = Self {
+        filter: true,
+        averaging_time: true,
+        interrupt: true,
+        thresholds: true,
+        gain: true,
+        line_out: true,
+    };
+
+    /// Returns true if the configurations agree on every field.
+    ///
+    pub const fn is_empty(&self) -> bool {
+        !(self.filter
+            || self.averaging_time
+            || self.interrupt
+            || self.thresholds
+            || self.gain
+            || self.line_out)
+    }
+
+    /// Returns true if a field stored in the CONTROL register disagrees.
+    ///
+    pub(crate) const fn control(&self) -> bool {
+        self.filter || self.interrupt || self.line_out
+    }
+
+    /// Also flags the fields of `config` whose register held an invalid
+    /// value, as flagged in `invalid`, so applying `config` rewrites them.
+    ///
+    /// The gain is only flagged if it is set in `config`.
+    ///
+    pub(crate) const fn rewriting(self, invalid: ConfigDiff, config: &Config) -> Self {
+        Self {
+            averaging_time: self.averaging_time || invalid.averaging_time,
+            gain: self.gain || (invalid.gain && config.gain.is_some()),
+            ..self
+        }
+    }
+}
+
+/// Maps a register read that failed with [`Error::InvalidRegisterValue`] to
+/// `None`, so a field holding an invalid value can be rewritten instead of
+/// failing.
+///
+pub(crate) fn invalid_as_none<T, E>(result: Result<T, Error<E>>) -> Result<Option<T>, Error<E>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(Error::InvalidRegisterValue { .. }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+impl<E, I2C> PaSpl<I2C>
+where
+    I2C: I2cBus<Error = E>,
+{
+    /// Applies the configuration `config` to the module.
+    ///
+    /// Reads the live configuration and only writes the registers holding
+    /// fields that differ from it. CONTROL is written last so interrupts are
+    /// only armed once the thresholds are in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidArgument`] if the minimum threshold is greater
+    /// than the maximum threshold.
+    ///
+    /// Returns [`Error::Unsupported`] if `config` sets the gain or enables the
+    /// line output and the variant does not support it.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn apply(&mut self, config: &Config) -> Result<(), Error<E>> {
+        self.apply_changes(config).map(|_| ())
+    }
+
+    /// Applies the configuration `config` like [`PaSpl::apply`] and returns
+    /// the fields that were written.
+    ///
+    pub(crate) fn apply_changes(&mut self, config: &Config) -> Result<ConfigDiff, Error<E>> {
+        let (min, max) = config.thresholds;
+        if min > max {
+            return Err(Error::InvalidArgument);
+        }
+        let variant = self.get_variant()?;
+        if (config.gain.is_some() && !variant.has_gain())
+            || (config.line_out && !variant.has_line_out())
+        {
+            return Err(Error::Unsupported(variant));
+        }
+
+        let (live, invalid) = self.read_live_config()?;
+        let diff = config.diff(&live).rewriting(invalid, config);
+        self.write_config(config, &diff)?;
+        Ok(diff)
+    }
+
+    /// Reads the live configuration of the module.
+    ///
+    /// The gain is only read on modules with adjustable gain.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidRegisterValue`] if TAVG or GAIN holds a value
+    /// that is not a valid averaging time or gain.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn read_config(&mut self) -> Result<Config, Error<E>> {
+        let control = self.get_control_register()?;
+        let averaging_time = self.get_avg_time()?;
+        let thresholds = self.get_threshold_window()?;
+        let gain = if self.get_variant()?.has_gain() {
+            Some(self.get_gain()?)
+        } else {
+            None
+        };
+
+        Ok(Config {
+            filter: control.filter(),
+            averaging_time,
+            interrupt: control.interrupt_mode(),
+            thresholds,
+            gain,
+            line_out: control.line_out(),
+        })
+    }
+
+    /// Reads the live configuration like [`PaSpl::read_config`], but does not
+    /// fail on a TAVG or GAIN register holding an invalid value.
+    ///
+    /// Returns the configuration with each invalid field set to its power-up
+    /// default, or `None` for the gain, along with the fields that were
+    /// invalid.
+    ///
+    pub(crate) fn read_live_config(&mut self) -> Result<(Config, ConfigDiff), Error<E>> {
+        let control = self.get_control_register()?;
+        let averaging_time = invalid_as_none(self.get_avg_time())?;
+        let thresholds = self.get_threshold_window()?;
+        let gain = if self.get_variant()?.has_gain() {
+            invalid_as_none(self.get_gain())?.map(Some)
+        } else {
+            Some(None)
+        };
+
+        let config = Config {
+            filter: control.filter(),
+            averaging_time: averaging_time.unwrap_or(AveragingTime::DEFAULT),
+            interrupt: control.interrupt_mode(),
+            thresholds,
+            gain: gain.flatten(),
+            line_out: control.line_out(),
+        };
+        let invalid = ConfigDiff {
+            averaging_time: averaging_time.is_none(),
+            gain: gain.is_none(),
+            ..ConfigDiff::default()
+        };
+        Ok((config, invalid))
+    }
+
+    /// Writes the fields of `config` flagged in `diff` to the module.
+    ///
+    pub(crate) fn write_config(
+        &mut self,
+        config: &Config,
+        diff: &ConfigDiff,
+    ) -> Result<(), Error<E>> {
+        if diff.averaging_time {
+            self.write_two_bytes(REG_TAVG_HIGH, &config.averaging_time.ms().to_be_bytes())?;
+        }
+        if diff.thresholds {
+            let (min, max) = config.thresholds;
+            self.write_two_bytes(REG_THR_MIN, &[min, max])?;
+        }
+        if let (true, Some(gain)) = (diff.gain, config.gain) {
+            self.write_byte(REG_GAIN, gain.steps())?;
+        }
+        if diff.control() {
+            self.write_byte(REG_CONTROL, config.control_register().into_bits())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, ConfigDiff, InterruptMode};
+    use crate::mock::{read_registers, I2cMock, I2cTransaction};
+    use crate::{
+        AveragingTime, Error, FilterSetting, Gain, PaSpl, Variant, DEVICE_ADDR_DEFAULT,
+        REG_CONTROL, REG_CONTROL_DEFAULT, REG_GAIN, REG_TAVG_HIGH, REG_THR_MIN, REG_VERSION,
+    };
+
+    /// Expectations for reading the power-up configuration of a module with
+    /// the VERSION register value `version`.
+    fn read_defaults_expectations(version: u8) -> Vec<I2cTransaction> {
+        let mut expectations = vec![
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_VERSION], vec![version]),
+            I2cTransaction::write_read(
+                DEVICE_ADDR_DEFAULT,
+                vec![REG_CONTROL],
+                vec![REG_CONTROL_DEFAULT],
+            ),
+        ];
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_TAVG_HIGH,
+            vec![0x03, 0xE8],
+        ));
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_THR_MIN,
+            vec![45, 85],
+        ));
+        expectations
+    }
+
+    #[test]
+    fn confirm_default_is_power_up_config() {
+        let config = Config::default();
+        assert_eq!(FilterSetting::AWeighting, config.filter);
+        assert_eq!(AveragingTime::SLOW, config.averaging_time);
+        assert_eq!(InterruptMode::Disabled, config.interrupt);
+        assert_eq!((45, 85), config.thresholds);
+        assert_eq!(None, config.gain);
+        assert!(!config.line_out);
+        assert_eq!(REG_CONTROL_DEFAULT, config.control_register().into_bits());
+    }
+
+    #[test]
+    fn confirm_diff() {
+        let desired = Config {
+            interrupt: InterruptMode::Threshold,
+            gain: Some(Gain::MAX),
+            ..Config::default()
+        };
+        let live = Config::default();
+
+        let diff = desired.diff(&live);
+        assert_eq!(
+            ConfigDiff {
+                interrupt: true,
+                gain: true,
+                ..ConfigDiff::default()
+            },
+            diff
+        );
+        assert!(!diff.is_empty());
+        // An unset gain is not compared.
+        assert!(!live.diff(&desired).gain);
+        assert!(live.diff(&live).is_empty());
+    }
+
+    #[test]
+    fn confirm_apply_writes_only_changes() {
+        let mut expectations = read_defaults_expectations(0x32);
+        expectations.extend([
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_THR_MIN, 40, 95]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0b0001_1010]),
+        ]);
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let config = Config {
+            interrupt: InterruptMode::Threshold,
+            thresholds: (40, 95),
+            ..Config::default()
+        };
+        pa_spl.apply(&config).unwrap();
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_apply_unchanged_config_writes_nothing() {
+        let expectations = read_defaults_expectations(0x31);
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        pa_spl.apply(&Config::default()).unwrap();
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_apply_rewrites_invalid_registers() {
+        let mut expectations = vec![
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_VERSION], vec![0x81]),
+            I2cTransaction::write_read(
+                DEVICE_ADDR_DEFAULT,
+                vec![REG_CONTROL],
+                vec![REG_CONTROL_DEFAULT],
+            ),
+        ];
+        // TAVG = 0 and GAIN = 0xff are out of range.
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_TAVG_HIGH,
+            vec![0x00, 0x00],
+        ));
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_THR_MIN,
+            vec![45, 85],
+        ));
+        expectations.extend([
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_GAIN], vec![0xff]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_TAVG_HIGH, 0x03, 0xE8]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_GAIN, 0]),
+        ]);
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let config = Config {
+            gain: Gain::from_steps(0),
+            ..Config::default()
+        };
+        pa_spl.apply(&config).unwrap();
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_apply_rejects_unsupported_config() {
+        let expectations = vec![I2cTransaction::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_VERSION],
+            vec![0x32],
+        )];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let config = Config {
+            gain: Some(Gain::DEFAULT),
+            ..Config::default()
+        };
+        assert_eq!(
+            Err(Error::Unsupported(Variant::MemsSpectrumAnalyzer)),
+            pa_spl.apply(&config)
+        );
+        let config = Config {
+            thresholds: (90, 40),
+            ..Config::default()
+        };
+        assert_eq!(Err(Error::InvalidArgument), pa_spl.apply(&config));
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_read_config() {
+        let mut expectations = vec![I2cTransaction::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_CONTROL],
+            vec![0b0010_0100],
+        )];
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_TAVG_HIGH,
+            vec![0x00, 0x7D],
+        ));
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_THR_MIN,
+            vec![50, 90],
+        ));
+        expectations.extend([
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_VERSION], vec![0x81]),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_GAIN], vec![30]),
+        ]);
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let config = pa_spl.read_config().unwrap();
+        assert_eq!(
+            Config {
+                filter: FilterSetting::CWeighting,
+                averaging_time: AveragingTime::FAST,
+                interrupt: InterruptMode::Disabled,
+                thresholds: (50, 90),
+                gain: Gain::from_steps(30),
+                line_out: true,
+            },
+            config
+        );
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+}
@@ -0,0 +1,229 @@
+//! Register map conformance suite.
+//!
+//! Checks the register constants, default values, and access types defined in
+//! the driver against a table transcribed by hand from the vendor programming
+//! manual (`docs/vendor/programming-manual.md`). The table must only ever be
+//! changed by re-transcribing it from the manual, never to match the driver.
+
+use super::*;
+
+/// A register entry as published in the vendor programming manual.
+struct ManualRegister {
+    name: &'static str,
+    addr: u8,
+    /// Default value, or `None` if the manual lists it as undefined, unique, or
+    /// variant specific.
+    default: Option<u8>,
+    access: Access,
+}
+
+/// Register map transcribed from the "I2C Decibel Meter Module Register Map"
+/// section of the vendor programming manual.
+#[rustfmt::skip]
+const MANUAL_REGISTER_MAP: &[ManualRegister] = &[
+    ManualRegister { name: "VERSION", addr: 0x00, default: None, access: Access::ReadOnly },
+    ManualRegister { name: "ID3", addr: 0x01, default: None, access: Access::ReadOnly },
+    ManualRegister { name: "ID2", addr: 0x02, default: None, access: Access::ReadOnly },
+    ManualRegister { name: "ID1", addr: 0x03, default: None, access: Access::ReadOnly },
+    ManualRegister { name: "ID0", addr: 0x04, default: None, access: Access::ReadOnly },
+    ManualRegister { name: "SCRATCH", addr: 0x05, default: Some(0xAA), access: Access::ReadWrite },
+    ManualRegister { name: "CONTROL", addr: 0x06, default: Some(0x02), access: Access::ReadWrite },
+    ManualRegister { name: "TAVG_HIGH", addr: 0x07, default: Some(0x03), access: Access::ReadWrite },
+    ManualRegister { name: "TAVG_LOW", addr: 0x08, default: Some(0xE8), access: Access::ReadWrite },
+    ManualRegister { name: "RESET", addr: 0x09, default: Some(0x00), access: Access::WriteOnly },
+    ManualRegister { name: "DECIBEL", addr: 0x0A, default: Some(0x00), access: Access::ReadOnly },
+    ManualRegister { name: "MIN", addr: 0x0B, default: None, access: Access::ReadOnly },
+    ManualRegister { name: "MAX", addr: 0x0C, default: None, access: Access::ReadOnly },
+    ManualRegister { name: "THR_MIN", addr: 0x0D, default: Some(45), access: Access::ReadWrite },
+    ManualRegister { name: "THR_MAX", addr: 0x0E, default: Some(85), access: Access::ReadWrite },
+    ManualRegister { name: "GAIN", addr: 0x0F, default: Some(18), access: Access::ReadWrite },
+];
+
+/// A block of consecutive registers as published in the vendor programming
+/// manual.
+struct ManualRegisterBlock {
+    name: &'static str,
+    first: u8,
+    last: u8,
+    default: Option<u8>,
+    access: Access,
+}
+
+/// Register blocks transcribed from the vendor programming manual.
+#[rustfmt::skip]
+const MANUAL_REGISTER_BLOCKS: &[ManualRegisterBlock] = &[
+    ManualRegisterBlock { name: "DBHISTORY", first: 0x14, last: 0x77, default: Some(0x00), access: Access::ReadOnly },
+    ManualRegisterBlock { name: "FREQ_64BINS", first: 0x78, last: 0xB7, default: None, access: Access::ReadOnly },
+];
+
+/// Looks up a register in the transcribed manual table by name.
+fn manual(name: &str) -> &'static ManualRegister {
+    MANUAL_REGISTER_MAP
+        .iter()
+        .find(|reg| reg.name == name)
+        .unwrap_or_else(|| panic!("{name} is not in the transcribed register map"))
+}
+
+/// Looks up a register block in the transcribed manual table by name.
+fn manual_block(name: &str) -> &'static ManualRegisterBlock {
+    MANUAL_REGISTER_BLOCKS
+        .iter()
+        .find(|block| block.name == name)
+        .unwrap_or_else(|| panic!("{name} is not in the transcribed register blocks"))
+}
+
+/// Looks up the access type of an address in the transcribed manual tables.
+fn manual_access_at(addr: u8) -> Option<Access> {
+    MANUAL_REGISTER_MAP
+        .iter()
+        .find(|reg| reg.addr == addr)
+        .map(|reg| reg.access)
+        .or_else(|| {
+            MANUAL_REGISTER_BLOCKS
+                .iter()
+                .find(|block| (block.first..=block.last).contains(&addr))
+                .map(|block| block.access)
+        })
+}
+
+#[test]
+fn confirm_register_addresses() {
+    let driver_registers: &[(&str, u8)] = &[
+        ("VERSION", REG_VERSION),
+        ("ID3", REGS_DEVICE_ID[0]),
+        ("ID2", REGS_DEVICE_ID[1]),
+        ("ID1", REGS_DEVICE_ID[2]),
+        ("ID0", REGS_DEVICE_ID[3]),
+        ("SCRATCH", REG_SCRATCH),
+        ("CONTROL", REG_CONTROL),
+        ("TAVG_HIGH", REG_TAVG_HIGH),
+        ("TAVG_LOW", REG_TAVG_LOW),
+        ("RESET", REG_RESET),
+        ("DECIBEL", REG_DECIBEL),
+        ("MIN", REG_MIN),
+        ("MAX", REG_MAX),
+        ("THR_MIN", REG_THR_MIN),
+        ("THR_MAX", REG_THR_MAX),
+        ("GAIN", REG_GAIN),
+    ];
+
+    for (name, addr) in driver_registers {
+        assert_eq!(manual(name).addr, *addr, "{name} register address");
+    }
+}
+
+#[test]
+fn confirm_register_blocks() {
+    let history = manual_block("DBHISTORY");
+    assert_eq!(history.first, REG_DBHISTORY_0);
+    assert_eq!(history.last, REG_DBHISTORY_99);
+    assert_eq!((history.last - history.first) as usize + 1, HISTORY_LEN);
+    assert_eq!(Some(0x00), history.default);
+
+    let spectrum = manual_block("FREQ_64BINS");
+    assert_eq!(spectrum.first, REG_FREQ_64BINS_0);
+    assert_eq!(spectrum.last, REG_FREQ_64BINS_63);
+    assert_eq!((spectrum.last - spectrum.first) as usize + 1, SPECTRUM_BINS);
+}
+
+#[test]
+fn confirm_register_defaults() {
+    let driver_defaults: &[(&str, u8)] = &[
+        ("SCRATCH", REG_SCRATCH_DEFAULT),
+        ("CONTROL", REG_CONTROL_DEFAULT),
+        ("TAVG_HIGH", (REG_AVERAGING_TIME_DEFAULT_MS >> 8) as u8),
+        ("TAVG_LOW", (REG_AVERAGING_TIME_DEFAULT_MS & 0xFF) as u8),
+        ("RESET", REG_RESET_DEFAULT),
+        ("THR_MIN", REG_THR_MIN_DEFAULT),
+        ("THR_MAX", REG_THR_MAX_DEFAULT),
+        ("GAIN", REG_GAIN_DEFAULT),
+    ];
+
+    for (name, default) in driver_defaults {
+        assert_eq!(manual(name).default, Some(*default), "{name} default value");
+    }
+}
+
+#[test]
+fn confirm_register_access_types() {
+    for addr in 0..=u8::MAX {
+        let expected = manual_access_at(addr);
+        assert_eq!(
+            expected,
+            register_access(addr),
+            "access type at {addr:#04x}"
+        );
+    }
+}
+
+#[test]
+fn confirm_min_max_are_not_thresholds() {
+    // Regression check: MIN/MAX hold the measured extremes, THR_MIN/THR_MAX
+    // hold the interrupt window and must never alias them.
+    assert_ne!(REG_MIN, REG_THR_MIN);
+    assert_ne!(REG_MAX, REG_THR_MAX);
+    assert_eq!(Access::ReadOnly, register_access(REG_MIN).unwrap());
+    assert_eq!(Access::ReadOnly, register_access(REG_MAX).unwrap());
+}
+
+#[test]
+fn confirm_control_register_default_decodes() {
+    // Power-up defaults per the manual: A-weighting, interrupt disabled.
+    let reg_control = ControlRegister::from_bits(REG_CONTROL_DEFAULT);
+    assert_eq!(FilterSetting::AWeighting, reg_control.filter_setting());
+    assert!(!reg_control.power_down());
+    assert!(!reg_control.interrupt_enable());
+    assert!(!reg_control.interrupt_type());
+}
+
+#[test]
+fn confirm_control_register_bit_positions() {
+    let mut reg_control = ControlRegister::new();
+    reg_control.set_power_down(true);
+    assert_eq!(0b0000_0001, reg_control.into_bits());
+
+    let mut reg_control = ControlRegister::new();
+    reg_control.set_filter(FilterSetting::CWeighting);
+    assert_eq!(0b0000_0100, reg_control.into_bits());
+
+    let mut reg_control = ControlRegister::new();
+    reg_control.set_interrupt_enable(true);
+    assert_eq!(0b0000_1000, reg_control.into_bits());
+
+    let mut reg_control = ControlRegister::new();
+    reg_control.set_interrupt_type(true);
+    assert_eq!(0b0001_0000, reg_control.into_bits());
+
+    let mut reg_control = ControlRegister::new();
+    reg_control.set_line_out(true);
+    assert_eq!(0b0010_0000, reg_control.into_bits());
+
+    let mut reg_control = ControlRegister::new();
+    reg_control.set_interrupt_mode(InterruptMode::History);
+    assert_eq!(0b0000_1000, reg_control.into_bits());
+
+    let mut reg_control = ControlRegister::new();
+    reg_control.set_interrupt_mode(InterruptMode::Threshold);
+    assert_eq!(0b0001_1000, reg_control.into_bits());
+    assert_eq!(InterruptMode::Threshold, reg_control.interrupt_mode());
+}
+
+#[test]
+fn confirm_reset_register_bit_positions() {
+    assert_eq!(
+        0b0000_0001,
+        ResetRegister::new().with_clear_interrupt(true).into_bits()
+    );
+    assert_eq!(
+        0b0000_0010,
+        ResetRegister::new().with_clear_min_max(true).into_bits()
+    );
+    assert_eq!(
+        0b0000_0100,
+        ResetRegister::new().with_clear_history(true).into_bits()
+    );
+    assert_eq!(
+        0b0000_1000,
+        ResetRegister::new().with_system_reset(true).into_bits()
+    );
+}
@@ -0,0 +1,204 @@
+//! Discovery of modules on an I2C bus.
+
+use core::ops::RangeInclusive;
+
+use defmt::Format;
+
+use crate::hal::I2cBus;
+use crate::{verify_scratch, Error, Operation, Variant, Version, REGS_DEVICE_ID, REG_VERSION};
+
+/// The 7-bit I2C addresses that are not reserved by the I2C specification.
+pub const SCAN_ADDRS: RangeInclusive<u8> = 0x08..=0x77;
+
+/// A PA SPL Module found on the bus by [`discover`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Format)]
+pub struct DiscoveredModule {
+    /// I2C address of the module.
+    pub address: u8,
+    /// Product variant of the module.
+    pub variant: Variant,
+    /// 32-bit device ID from registers ID3-ID0.
+    pub device_id: u32,
+}
+
+/// Probes each address in `addrs` for a PA SPL Module.
+///
+/// The VERSION register is read first, and a device is only considered a
+/// module if its hardware version is known, see [`Version::is_known_hardware`].
+/// The SCRATCH register must then pass the write-read-verify check recommended
+/// by the manual, and the ID registers are read to report the device ID.
+/// Addresses that do not respond, or fail any of these steps, are skipped.
+///
+/// Modules with firmware that is not documented by the vendor are still
+/// reported, with [`Variant::Unknown`] as their variant.
+///
+/// The probe only writes to register 0x05 of devices whose VERSION register
+/// (0x00) holds a known hardware version, and restores its previous value
+/// afterwards. Other devices on the bus are only read.
+///
+/// The returned iterator probes one address per call to `next`.
+///
+pub fn discover<I2C, A>(i2c: &mut I2C, addrs: A) -> Discover<'_, I2C, A::IntoIter>
+where
+    I2C: I2cBus,
+    A: IntoIterator<Item = u8>,
+{
+    Discover {
+        i2c,
+        addrs: addrs.into_iter(),
+    }
+}
+
+/// Iterator over the modules found on the bus, returned by [`discover`].
+pub struct Discover<'a, I2C, A>
+where
+    I2C: I2cBus,
+    A: Iterator<Item = u8>,
+{
+    i2c: &'a mut I2C,
+    addrs: A,
+}
+
+impl<I2C, A> Iterator for Discover<'_, I2C, A>
+where
+    I2C: I2cBus,
+    A: Iterator<Item = u8>,
+{
+    type Item = DiscoveredModule;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for address in self.addrs.by_ref() {
+            if let Ok(Some(module)) = probe(self.i2c, address) {
+                return Some(module);
+            }
+        }
+        None
+    }
+}
+
+/// Probes a single address for a PA SPL Module.
+///
+/// Returns `Ok(None)` if a device responds but its VERSION register does not
+/// hold a known hardware version or it fails the SCRATCH check.
+///
+fn probe<I2C>(i2c: &mut I2C, address: u8) -> Result<Option<DiscoveredModule>, Error<I2C::Error>>
+where
+    I2C: I2cBus,
+{
+    let mut version = [0; 1];
+    i2c.write_read(address, &[REG_VERSION], &mut version)
+        .map_err(Error::i2c(REG_VERSION, Operation::Read))?;
+    let version = Version::from_bits(version[0]);
+    if !version.is_known_hardware() {
+        return Ok(None);
+    }
+
+    if !verify_scratch(i2c, address)? {
+        return Ok(None);
+    }
+
+    let mut device_id = [0; 4];
+    i2c.read_registers(address, REGS_DEVICE_ID[0], &mut device_id)
+        .map_err(Error::i2c(REGS_DEVICE_ID[0], Operation::Read))?;
+
+    Ok(Some(DiscoveredModule {
+        address,
+        variant: version.variant(),
+        device_id: u32::from_be_bytes(device_id),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{discover, DiscoveredModule};
+    use crate::mock::{read_registers, with_bus_error, I2cMock, I2cTransaction};
+    use crate::{Variant, REGS_DEVICE_ID, REG_SCRATCH, REG_VERSION};
+
+    /// Expectations for probing a module that passes the SCRATCH check.
+    fn module_expectations(addr: u8, version: u8, device_id: [u8; 4]) -> Vec<I2cTransaction> {
+        let mut expectations = vec![
+            I2cTransaction::write_read(addr, vec![REG_VERSION], vec![version]),
+            I2cTransaction::write_read(addr, vec![REG_SCRATCH], vec![0xaa]),
+            I2cTransaction::write(addr, vec![REG_SCRATCH, 0x55]),
+            I2cTransaction::write_read(addr, vec![REG_SCRATCH], vec![0x55]),
+            I2cTransaction::write(addr, vec![REG_SCRATCH, 0xaa]),
+        ];
+        expectations.extend(read_registers(addr, REGS_DEVICE_ID[0], device_id.to_vec()));
+        expectations
+    }
+
+    #[test]
+    fn confirm_discover() {
+        let mut expectations = vec![with_bus_error(I2cTransaction::write_read(
+            0x47,
+            vec![REG_VERSION],
+            vec![0],
+        ))];
+        expectations.extend(module_expectations(0x48, 0x32, [0x01, 0x02, 0x03, 0x04]));
+        // Another device with a known hardware version in register 0x00 that
+        // does not hold the value written to register 0x05.
+        expectations.extend([
+            I2cTransaction::write_read(0x49, vec![REG_VERSION], vec![0x31]),
+            I2cTransaction::write_read(0x49, vec![REG_SCRATCH], vec![0x00]),
+            I2cTransaction::write(0x49, vec![REG_SCRATCH, 0xff]),
+            I2cTransaction::write_read(0x49, vec![REG_SCRATCH], vec![0x00]),
+            I2cTransaction::write(0x49, vec![REG_SCRATCH, 0x00]),
+        ]);
+        expectations.extend(module_expectations(0x4a, 0x81, [0xde, 0xad, 0xbe, 0xef]));
+        let mut i2c_mock = I2cMock::new(&expectations);
+
+        let mut modules = discover(&mut i2c_mock, 0x47..=0x4a);
+        assert_eq!(
+            Some(DiscoveredModule {
+                address: 0x48,
+                variant: Variant::MemsSpectrumAnalyzer,
+                device_id: 0x01020304,
+            }),
+            modules.next()
+        );
+        assert_eq!(
+            Some(DiscoveredModule {
+                address: 0x4a,
+                variant: Variant::ExternalMic,
+                device_id: 0xdeadbeef,
+            }),
+            modules.next()
+        );
+        assert_eq!(None, modules.next());
+
+        i2c_mock.done();
+    }
+
+    #[test]
+    fn confirm_discover_skips_unknown_hardware() {
+        // A device whose register 0x00 does not hold a known hardware version,
+        // e.g. an EEPROM, is only read.
+        let expectations = vec![I2cTransaction::write_read(
+            0x50,
+            vec![REG_VERSION],
+            vec![0xff],
+        )];
+        let mut i2c_mock = I2cMock::new(&expectations);
+
+        assert_eq!(None, discover(&mut i2c_mock, [0x50]).next());
+
+        i2c_mock.done();
+    }
+
+    #[test]
+    fn confirm_discover_reports_unknown_firmware() {
+        let expectations = module_expectations(0x48, 0x34, [0x01, 0x02, 0x03, 0x04]);
+        let mut i2c_mock = I2cMock::new(&expectations);
+
+        assert_eq!(
+            Some(DiscoveredModule {
+                address: 0x48,
+                variant: Variant::Unknown,
+                device_id: 0x01020304,
+            }),
+            discover(&mut i2c_mock, [0x48]).next()
+        );
+
+        i2c_mock.done();
+    }
+}
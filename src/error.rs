@@ -0,0 +1,230 @@
+//! Errors of the driver.
+
+use core::fmt;
+
+use defmt::Format;
+
+use crate::hal::{BusError, ErrorKind};
+use crate::Variant;
+
+/// Direction of a failed register access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Format)]
+pub enum Operation {
+    /// Reading one or more registers.
+    Read,
+    /// Writing one or more registers.
+    Write,
+}
+
+/// A driver error.
+#[derive(Debug, PartialEq, Eq, Format)]
+pub enum Error<E> {
+    /// I2C bus error.
+    I2c {
+        /// Error returned by the bus.
+        source: E,
+        /// Address of the (first) register accessed.
+        register: u8,
+        /// Direction of the access.
+        operation: Operation,
+    },
+    /// Buffer overflow.
+    BufferOverflow,
+    /// Invalid argument.
+    InvalidArgument,
+    /// Operation not supported by this variant of the module.
+    Unsupported(Variant),
+    /// A register holds a value outside of its documented range.
+    InvalidRegisterValue {
+        /// Address of the (first) register read.
+        register: u8,
+        /// Raw value read from the register(s).
+        value: u16,
+    },
+    /// The device at the address failed the SCRATCH write-read-verify check.
+    NotPresent,
+    /// The module is not the expected variant.
+    VariantMismatch {
+        /// Variant the module was expected to be.
+        expected: Variant,
+        /// Variant read from the VERSION register.
+        found: Variant,
+    },
+    /// The module is not ready, e.g. the decibel reading is not valid yet
+    /// after power-up.
+    NotReady,
+    /// A verified write was read back with a different value.
+    ///
+    /// The reserved bits of CONTROL are not compared, but are reported as
+    /// read.
+    VerifyFailed {
+        /// Address of the register that differs.
+        register: u8,
+        /// Value written to the register.
+        written: u8,
+        /// Value read back from the register.
+        read: u8,
+    },
+    /// A value was not written because it sets reserved bits of the register
+    /// or holds a reserved encoding, e.g. after a corrupted read.
+    ReservedBits {
+        /// Address of the register.
+        register: u8,
+        /// Value refused.
+        value: u8,
+    },
+}
+
+impl<E> Error<E> {
+    /// Returns a function wrapping a bus error of an access of `register`.
+    ///
+    pub(crate) fn i2c(register: u8, operation: Operation) -> impl FnOnce(E) -> Self {
+        move |source| Self::I2c {
+            source,
+            register,
+            operation,
+        }
+    }
+}
+
+impl<E> Error<E>
+where
+    E: BusError,
+{
+    /// Gets the kind of the bus error.
+    ///
+    /// Returns `None` if this is not a bus error.
+    ///
+    pub fn kind(&self) -> Option<ErrorKind> {
+        match self {
+            Self::I2c { source, .. } => Some(source.kind()),
+            _ => None,
+        }
+    }
+}
+
+impl<E> fmt::Display for Error<E>
+where
+    E: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::I2c {
+                source,
+                register,
+                operation: Operation::Read,
+            } => write!(f, "I2C error reading register {register:#04x}: {source:?}"),
+            Self::I2c {
+                source,
+                register,
+                operation: Operation::Write,
+            } => write!(f, "I2C error writing register {register:#04x}: {source:?}"),
+            Self::BufferOverflow => f.write_str("buffer overflow"),
+            Self::InvalidArgument => f.write_str("invalid argument"),
+            Self::Unsupported(variant) => {
+                write!(f, "operation not supported by variant {variant:?}")
+            }
+            Self::InvalidRegisterValue { register, value } => write!(
+                f,
+                "register {register:#04x} holds invalid value {value:#06x}"
+            ),
+            Self::NotPresent => f.write_str("no PA SPL Module present at the address"),
+            Self::VariantMismatch { expected, found } => {
+                write!(f, "expected variant {expected:?}, found {found:?}")
+            }
+            Self::NotReady => f.write_str("module not ready"),
+            Self::VerifyFailed {
+                register,
+                written,
+                read,
+            } => write!(
+                f,
+                "register {register:#04x} read back {read:#04x} after writing {written:#04x}"
+            ),
+            Self::ReservedBits { register, value } => write!(
+                f,
+                "value {value:#04x} sets reserved bits of register {register:#04x}"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E> std::error::Error for Error<E> where E: fmt::Debug {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Operation};
+    use crate::hal::ErrorKind;
+    use crate::mock::{with_bus_error, I2cMock, I2cTransaction};
+    use crate::{AveragingTime, PaSpl, Variant, DEVICE_ADDR_DEFAULT, REG_TAVG_HIGH, REG_THR_MIN};
+
+    #[test]
+    fn confirm_i2c_error_context() {
+        let expectations = vec![
+            with_bus_error(I2cTransaction::write_read(
+                DEVICE_ADDR_DEFAULT,
+                vec![REG_THR_MIN],
+                vec![0],
+            )),
+            with_bus_error(I2cTransaction::write(
+                DEVICE_ADDR_DEFAULT,
+                vec![REG_TAVG_HIGH, 0x00, 0x7D],
+            )),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let error = pa_spl.get_threshold_min().unwrap_err();
+        assert!(matches!(
+            error,
+            Error::I2c {
+                register: REG_THR_MIN,
+                operation: Operation::Read,
+                ..
+            }
+        ));
+        assert_eq!(Some(ErrorKind::Other), error.kind());
+        let error = pa_spl.set_avg_time(AveragingTime::FAST).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::I2c {
+                register: REG_TAVG_HIGH,
+                operation: Operation::Write,
+                ..
+            }
+        ));
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_display() {
+        let error: Error<ErrorKind> = Error::I2c {
+            source: ErrorKind::Other,
+            register: 0x0a,
+            operation: Operation::Read,
+        };
+        assert_eq!("I2C error reading register 0x0a: Other", error.to_string());
+        assert_eq!(None, Error::<ErrorKind>::NotReady.kind());
+
+        let error: Error<ErrorKind> = Error::VariantMismatch {
+            expected: Variant::ExternalMic,
+            found: Variant::MemsLts,
+        };
+        assert_eq!(
+            "expected variant ExternalMic, found MemsLts",
+            error.to_string()
+        );
+
+        let error: Error<ErrorKind> = Error::InvalidRegisterValue {
+            register: REG_TAVG_HIGH,
+            value: 0,
+        };
+        assert_eq!(
+            "register 0x07 holds invalid value 0x0000",
+            error.to_string()
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Microphone gain of the GAIN register.
+
+use defmt::Format;
+
+use crate::REG_GAIN_DEFAULT;
+
+/// Analog gain applied to the external microphone.
+///
+/// The gain is set in 0.5 dB steps from 0 steps (+0.0 dB) to 95 steps
+/// (+47.5 dB), as accepted by the GAIN register. A `Gain` can only hold a
+/// value in that range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Format)]
+pub struct Gain(u8);
+
+impl Gain {
+    /// Maximum number of 0.5 dB steps.
+    pub const MAX_STEPS: u8 = 95;
+    /// Size of one step in dB.
+    pub const STEP_DB: f32 = 0.5;
+    /// Minimum gain, +0.0 dB.
+    pub const MIN: Self = Self(0);
+    /// Maximum gain, +47.5 dB.
+    pub const MAX: Self = Self(Self::MAX_STEPS);
+    /// Power-up default gain, +9.0 dB, which suits the microphone supplied with
+    /// the module.
+    pub const DEFAULT: Self = Self(REG_GAIN_DEFAULT);
+
+    /// Creates a gain from a number of 0.5 dB steps.
+    ///
+    /// Returns `None` if `steps` is greater than [`Gain::MAX_STEPS`].
+    ///
+    pub const fn from_steps(steps: u8) -> Option<Self> {
+        if steps <= Self::MAX_STEPS {
+            Some(Self(steps))
+        } else {
+            None
+        }
+    }
+
+    /// Creates a gain from a value in dB.
+    ///
+    /// Returns `None` if `db` is not a multiple of 0.5 dB from +0.0 dB to
+    /// +47.5 dB.
+    ///
+    pub fn from_db(db: f32) -> Option<Self> {
+        let steps = db / Self::STEP_DB;
+        if !(0.0..=Self::MAX_STEPS as f32).contains(&steps) || steps as u8 as f32 != steps {
+            return None;
+        }
+        Self::from_steps(steps as u8)
+    }
+
+    /// Gets the gain as a number of 0.5 dB steps, the value of the GAIN
+    /// register.
+    ///
+    pub const fn steps(self) -> u8 {
+        self.0
+    }
+
+    /// Gets the gain in dB.
+    ///
+    pub fn db(self) -> f32 {
+        self.0 as f32 * Self::STEP_DB
+    }
+}
+
+impl Default for Gain {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl TryFrom<u8> for Gain {
+    type Error = u8;
+
+    /// Creates a gain from a number of 0.5 dB steps, returning the rejected
+    /// value if it is out of range.
+    fn try_from(steps: u8) -> Result<Self, Self::Error> {
+        Self::from_steps(steps).ok_or(steps)
+    }
+}
+
+impl From<Gain> for u8 {
+    fn from(gain: Gain) -> Self {
+        gain.steps()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Gain;
+
+    #[test]
+    fn confirm_from_steps() {
+        assert_eq!(Some(0.0), Gain::from_steps(0).map(Gain::db));
+        assert_eq!(Some(9.0), Gain::from_steps(18).map(Gain::db));
+        assert_eq!(Some(47.5), Gain::from_steps(95).map(Gain::db));
+        assert_eq!(None, Gain::from_steps(96));
+        assert_eq!(None, Gain::from_steps(u8::MAX));
+        assert_eq!(Err(96), Gain::try_from(96));
+    }
+
+    #[test]
+    fn confirm_from_db() {
+        assert_eq!(Some(Gain::MIN), Gain::from_db(0.0));
+        assert_eq!(Some(Gain::DEFAULT), Gain::from_db(9.0));
+        assert_eq!(Some(43), Gain::from_db(21.5).map(Gain::steps));
+        assert_eq!(Some(Gain::MAX), Gain::from_db(47.5));
+        assert_eq!(None, Gain::from_db(48.0));
+        assert_eq!(None, Gain::from_db(-0.5));
+        assert_eq!(None, Gain::from_db(21.3));
+        assert_eq!(None, Gain::from_db(f32::NAN));
+        assert_eq!(None, Gain::from_db(f32::INFINITY));
+    }
+
+    #[test]
+    fn confirm_round_trip() {
+        for steps in 0..=Gain::MAX_STEPS {
+            let gain = Gain::from_steps(steps).unwrap();
+            assert_eq!(Some(gain), Gain::from_db(gain.db()));
+            assert_eq!(steps, u8::from(gain));
+        }
+    }
+}
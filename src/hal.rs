@@ -0,0 +1,253 @@
+//! Traits used by the driver, implemented for the embedded-hal generations
+//! enabled by feature.
+//!
+//! With the `eh1` feature (default) they are implemented for every type that
+//! implements the embedded-hal 1.0 traits. With the `eh0` feature they are
+//! implemented for `Eh0`, a wrapper of a type that implements the
+//! embedded-hal 0.2 traits. The features are additive, so both can be
+//! enabled. HAL implementations do not need to implement these traits
+//! themselves.
+
+pub use embedded_hal::i2c::ErrorKind;
+
+/// An error of an I2C bus that can be classified by kind.
+///
+/// Implemented for every `embedded_hal::i2c::Error`. The embedded-hal 0.2
+/// traits have no error kinds, so with `eh0` every error is an `Eh0Error` of
+/// kind [`ErrorKind::Other`].
+pub trait BusError {
+    /// Gets the kind of the error.
+    fn kind(&self) -> ErrorKind;
+}
+
+/// A blocking I2C bus.
+///
+/// Implemented for `embedded_hal::i2c::I2c` (`eh1`) or for
+/// `Eh0` of `embedded_hal::blocking::i2c::{Write, WriteRead}` (`eh0`).
+pub trait I2cBus {
+    /// Error type of the bus.
+    type Error;
+
+    /// Writes `bytes` to the device at `address`.
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error>;
+
+    /// Writes `bytes` to, then reads `buffer` from the device at `address`
+    /// with a repeated start.
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>;
+
+    /// Reads consecutive registers starting at `start_reg` into `buffer` from
+    /// the device at `address`.
+    ///
+    /// With embedded-hal 1.0 this is a single I2C transaction of a register
+    /// address write followed by a read.
+    fn read_registers(
+        &mut self,
+        address: u8,
+        start_reg: u8,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error>;
+}
+
+/// A digital input pin.
+///
+/// Implemented for `embedded_hal::digital::InputPin` (`eh1`) or for
+/// `Eh0` of `embedded_hal::digital::v2::InputPin` (`eh0`).
+pub trait InputPin {
+    /// Error type of the pin.
+    type Error;
+
+    /// Returns true if the pin is low.
+    fn is_low(&mut self) -> Result<bool, Self::Error>;
+}
+
+/// A blocking delay provider.
+///
+/// Implemented for `embedded_hal::delay::DelayNs` (`eh1`) or for
+/// `Eh0` of `embedded_hal::blocking::delay::DelayMs<u32>` (`eh0`).
+pub trait Delay {
+    /// Pauses execution for at least `ms` milliseconds.
+    fn delay_ms(&mut self, ms: u32);
+}
+
+impl<T> BusError for T
+where
+    T: embedded_hal::i2c::Error,
+{
+    fn kind(&self) -> ErrorKind {
+        embedded_hal::i2c::Error::kind(self)
+    }
+}
+
+#[cfg(feature = "eh1")]
+mod eh1 {
+    use embedded_hal::{delay, digital, i2c};
+
+    impl<T> super::I2cBus for T
+    where
+        T: i2c::I2c,
+    {
+        type Error = T::Error;
+
+        fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+            i2c::I2c::write(self, address, bytes)
+        }
+
+        fn write_read(
+            &mut self,
+            address: u8,
+            bytes: &[u8],
+            buffer: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            i2c::I2c::write_read(self, address, bytes, buffer)
+        }
+
+        fn read_registers(
+            &mut self,
+            address: u8,
+            start_reg: u8,
+            buffer: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            let start_reg = [start_reg];
+            let mut operations = [
+                i2c::Operation::Write(&start_reg),
+                i2c::Operation::Read(buffer),
+            ];
+            i2c::I2c::transaction(self, address, &mut operations)
+        }
+    }
+
+    impl<T> super::InputPin for T
+    where
+        T: digital::InputPin,
+    {
+        type Error = T::Error;
+
+        fn is_low(&mut self) -> Result<bool, Self::Error> {
+            digital::InputPin::is_low(self)
+        }
+    }
+
+    impl<T> super::Delay for T
+    where
+        T: delay::DelayNs,
+    {
+        fn delay_ms(&mut self, ms: u32) {
+            delay::DelayNs::delay_ms(self, ms)
+        }
+    }
+}
+
+#[cfg(feature = "eh0")]
+pub use eh0::{Eh0, Eh0Error};
+
+#[cfg(feature = "eh0")]
+mod eh0 {
+    use defmt::Format;
+    use embedded_hal_0_2::blocking::{delay, i2c};
+    use embedded_hal_0_2::digital::v2 as digital;
+
+    /// An embedded-hal 0.2 I2C bus, input pin or delay provider.
+    ///
+    /// Wrap a 0.2 peripheral in `Eh0` to use it with the driver, e.g.
+    /// `PaSpl::new(Eh0(i2c))`.
+    #[derive(Debug, Clone)]
+    pub struct Eh0<T>(pub T);
+
+    /// An error of an embedded-hal 0.2 I2C bus.
+    ///
+    /// The embedded-hal 0.2 traits have no error kinds, so its kind is always
+    /// [`ErrorKind::Other`](super::ErrorKind::Other).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Format)]
+    pub struct Eh0Error<E>(pub E);
+
+    impl<E> super::BusError for Eh0Error<E> {
+        fn kind(&self) -> super::ErrorKind {
+            super::ErrorKind::Other
+        }
+    }
+
+    impl<T, E> super::I2cBus for Eh0<T>
+    where
+        T: i2c::Write<Error = E> + i2c::WriteRead<Error = E>,
+    {
+        type Error = Eh0Error<E>;
+
+        fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+            i2c::Write::write(&mut self.0, address, bytes).map_err(Eh0Error)
+        }
+
+        fn write_read(
+            &mut self,
+            address: u8,
+            bytes: &[u8],
+            buffer: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            i2c::WriteRead::write_read(&mut self.0, address, bytes, buffer).map_err(Eh0Error)
+        }
+
+        fn read_registers(
+            &mut self,
+            address: u8,
+            start_reg: u8,
+            buffer: &mut [u8],
+        ) -> Result<(), Self::Error> {
+            i2c::WriteRead::write_read(&mut self.0, address, &[start_reg], buffer).map_err(Eh0Error)
+        }
+    }
+
+    impl<T> super::InputPin for Eh0<T>
+    where
+        T: digital::InputPin,
+    {
+        type Error = T::Error;
+
+        fn is_low(&mut self) -> Result<bool, Self::Error> {
+            digital::InputPin::is_low(&self.0)
+        }
+    }
+
+    impl<T> super::Delay for Eh0<T>
+    where
+        T: delay::DelayMs<u32>,
+    {
+        fn delay_ms(&mut self, ms: u32) {
+            delay::DelayMs::delay_ms(&mut self.0, ms)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "eh0", feature = "eh1"))]
+mod tests {
+    use super::{Eh0, Eh0Error};
+    use crate::{Error, PaSpl, DEVICE_ADDR_DEFAULT, REG_DECIBEL};
+    use embedded_hal_mock::eh0::i2c::{Mock as I2cMock0, Transaction as I2cTransaction0};
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock1, Transaction as I2cTransaction1};
+
+    #[test]
+    fn confirm_eh0_and_eh1_together() {
+        let expectations = [I2cTransaction0::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_DECIBEL],
+            vec![62],
+        )];
+        let mut pa_spl_eh0 = PaSpl::new(Eh0(I2cMock0::new(&expectations)));
+        let expectations = [I2cTransaction1::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_DECIBEL],
+            vec![63],
+        )];
+        let mut pa_spl_eh1 = PaSpl::new(I2cMock1::new(&expectations));
+
+        let decibel: Result<u8, Error<Eh0Error<_>>> = pa_spl_eh0.get_latest_decibel();
+        assert_eq!(Ok(62), decibel);
+        assert_eq!(Ok(63), pa_spl_eh1.get_latest_decibel());
+
+        pa_spl_eh0.release().0.done();
+        pa_spl_eh1.release().done();
+    }
+}
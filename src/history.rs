@@ -0,0 +1,90 @@
+//! Decibel history read from the DBHISTORY_0 to DBHISTORY_99 registers.
+
+use defmt::Format;
+
+/// Number of DBHISTORY registers.
+pub const HISTORY_LEN: usize = 100;
+
+/// Decibel history captured by the module.
+///
+/// The module pushes a new reading into the history queue every Tavg period.
+/// Entries are ordered newest first, so index 0 is DBHISTORY_0 (the latest
+/// reading) and index 99 is DBHISTORY_99 (the oldest reading). Entries that
+/// have not been filled since power-up or the last history clear read as 0.
+///
+/// A history returned from a partial read only holds the requested window of
+/// the queue; [`History::start`] gives the DBHISTORY index of its first entry.
+#[derive(Debug, Clone, PartialEq, Eq, Format)]
+pub struct History {
+    values: [u8; HISTORY_LEN],
+    start: usize,
+    len: usize,
+}
+
+impl History {
+    /// Creates a history from `values` read starting at DBHISTORY_`start`.
+    ///
+    /// `start + values.len()` must not exceed [`HISTORY_LEN`].
+    ///
+    pub(crate) fn from_slice(start: usize, values: &[u8]) -> Self {
+        let mut history = Self {
+            values: [0; HISTORY_LEN],
+            start,
+            len: values.len(),
+        };
+        history.values[..values.len()].copy_from_slice(values);
+        history
+    }
+
+    /// Gets the DBHISTORY index of the first entry.
+    ///
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Gets the number of entries.
+    ///
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the history holds no entries.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets the entries in decibels, newest first.
+    ///
+    pub fn as_slice(&self) -> &[u8] {
+        &self.values[..self.len]
+    }
+
+    /// Gets the entry in decibels stored in DBHISTORY_`index`.
+    ///
+    /// Returns `None` if the entry is outside of the window that was read.
+    ///
+    pub fn get(&self, index: usize) -> Option<u8> {
+        index
+            .checked_sub(self.start)
+            .and_then(|offset| self.as_slice().get(offset).copied())
+    }
+
+    /// Gets the newest entry in decibels.
+    ///
+    pub fn newest(&self) -> Option<u8> {
+        self.as_slice().first().copied()
+    }
+
+    /// Gets the oldest entry in decibels.
+    ///
+    pub fn oldest(&self) -> Option<u8> {
+        self.as_slice().last().copied()
+    }
+
+    /// Iterates over the entries in decibels, newest first.
+    ///
+    pub fn iter(&self) -> core::iter::Copied<core::slice::Iter<'_, u8>> {
+        self.as_slice().iter().copied()
+    }
+}
@@ -0,0 +1,150 @@
+//! INT pin of the module.
+
+use crate::hal::{Delay, I2cBus, InputPin};
+use crate::{Error, PaSpl};
+
+/// Interval in ms at which [`InterruptPin::wait_for_interrupt`] polls the pin.
+pub const INTERRUPT_POLL_INTERVAL_MS: u32 = 1;
+
+/// The open-drain INT pin of a PA SPL Module on the input pin `P`.
+///
+/// The module pulls the INT pin low to indicate a pending interrupt once the
+/// interrupt is enabled in the CONTROL register, e.g. with
+/// [`PaSpl::enable_threshold_interrupt`]. The pin stays low until the
+/// interrupt is cleared with [`InterruptPin::read_latest_and_acknowledge`], so the input needs
+/// a pull-up.
+pub struct InterruptPin<P>
+where
+    P: InputPin,
+{
+    pin: P,
+}
+
+impl<P> InterruptPin<P>
+where
+    P: InputPin,
+{
+    /// Initializes the INT pin of the module on the input pin `pin`.
+    ///
+    pub fn new(pin: P) -> Self {
+        Self { pin }
+    }
+
+    /// Returns true if an interrupt is pending, i.e. the INT pin is low.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the input pin if reading it fails.
+    ///
+    pub fn is_interrupt_pending(&mut self) -> Result<bool, P::Error> {
+        self.pin.is_low()
+    }
+
+    /// Blocks until an interrupt is pending.
+    ///
+    /// The pin is polled every [`INTERRUPT_POLL_INTERVAL_MS`] using `delay`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the input pin if reading it fails.
+    ///
+    pub fn wait_for_interrupt<D>(&mut self, delay: &mut D) -> Result<(), P::Error>
+    where
+        D: Delay,
+    {
+        while !self.is_interrupt_pending()? {
+            delay.delay_ms(INTERRUPT_POLL_INTERVAL_MS);
+        }
+        Ok(())
+    }
+
+    /// Reads the latest decibel reading, then acknowledges a pending
+    /// interrupt.
+    ///
+    /// Reads the DECIBEL register of `pa_spl`, then clears the interrupt by
+    /// setting the Clear Interrupt bit in the RESET register, which releases
+    /// the INT pin.
+    ///
+    /// Returns the latest decibel reading, not the reading that caused the
+    /// interrupt: DECIBEL may have been updated one or more averaging periods
+    /// after the threshold was crossed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn read_latest_and_acknowledge<E, I2C>(
+        &mut self,
+        pa_spl: &mut PaSpl<I2C>,
+    ) -> Result<u8, Error<E>>
+    where
+        I2C: I2cBus<Error = E>,
+    {
+        let decibel = pa_spl.get_latest_decibel()?;
+        pa_spl.clear_interrupt()?;
+        Ok(decibel)
+    }
+
+    /// Destroys this INT pin and releases the input pin.
+    ///
+    pub fn release(self) -> P {
+        self.pin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InterruptPin;
+    use crate::mock::{I2cMock, I2cTransaction, NoopDelay, PinMock, PinState, PinTransaction};
+    use crate::{PaSpl, DEVICE_ADDR_DEFAULT, REG_DECIBEL, REG_RESET};
+
+    #[test]
+    fn confirm_is_interrupt_pending() {
+        let pin_expectations = [
+            PinTransaction::get(PinState::High),
+            PinTransaction::get(PinState::Low),
+        ];
+        let mut int_pin = InterruptPin::new(PinMock::new(&pin_expectations));
+
+        assert!(!int_pin.is_interrupt_pending().unwrap());
+        assert!(int_pin.is_interrupt_pending().unwrap());
+
+        let mut pin = int_pin.release();
+        pin.done();
+    }
+
+    #[test]
+    fn confirm_wait_for_interrupt() {
+        let pin_expectations = [
+            PinTransaction::get(PinState::High),
+            PinTransaction::get(PinState::High),
+            PinTransaction::get(PinState::Low),
+        ];
+        let mut int_pin = InterruptPin::new(PinMock::new(&pin_expectations));
+
+        let result = int_pin.wait_for_interrupt(&mut NoopDelay::new());
+        assert!(result.is_ok());
+
+        let mut pin = int_pin.release();
+        pin.done();
+    }
+
+    #[test]
+    fn confirm_read_latest_and_acknowledge() {
+        let expectations = vec![
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_DECIBEL], vec![92]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_RESET, 0b0000_0001]),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+        let mut int_pin = InterruptPin::new(PinMock::new(&[]));
+
+        let decibel = int_pin.read_latest_and_acknowledge(&mut pa_spl).unwrap();
+        assert_eq!(92, decibel);
+
+        let mut mock = pa_spl.release();
+        mock.done();
+        let mut pin = int_pin.release();
+        pin.done();
+    }
+}
@@ -1,9 +1,52 @@
 #![doc = include_str!("../README.md")]
 #![cfg_attr(not(test), no_std)]
 
+#[cfg(all(feature = "std", not(test)))]
+extern crate std;
+
 use bitfield_struct::bitfield;
 use defmt::Format;
-use embedded_hal::blocking::i2c;
+use hal::I2cBus;
+
+#[cfg(not(any(feature = "eh0", feature = "eh1")))]
+compile_error!("one of the features `eh0` or `eh1` must be enabled");
+
+mod array;
+#[cfg(feature = "async")]
+mod asynch;
+mod averaging_time;
+mod builder;
+mod config;
+mod discover;
+mod error;
+mod gain;
+pub mod hal;
+mod history;
+mod interrupt;
+mod power;
+mod retry;
+mod self_test;
+mod settling;
+mod spectrum;
+mod version;
+
+pub use array::PaSplArray;
+#[cfg(feature = "async")]
+pub use asynch::{InterruptPinAsync, PaSplAsync};
+pub use averaging_time::AveragingTime;
+pub use builder::{NoPin, PaSplBuilder, WARM_UP_DELAY_MS};
+pub use config::{Config, ConfigDiff, InterruptMode};
+pub use discover::{discover, Discover, DiscoveredModule, SCAN_ADDRS};
+pub use error::{Error, Operation};
+pub use gain::Gain;
+pub use history::{History, HISTORY_LEN};
+pub use interrupt::{InterruptPin, INTERRUPT_POLL_INTERVAL_MS};
+pub use power::{SleepingPaSpl, RESET_DELAY_MS};
+pub use retry::{Retry, RetryPolicy, RetryStats};
+pub use self_test::SelfTestReport;
+pub use settling::{Clock, ClockedPaSpl, Reading, Validity};
+pub use spectrum::{Spectrum, SPECTRUM_BANDWIDTH_HZ, SPECTRUM_BINS, SPECTRUM_BIN_WIDTH_HZ};
+pub use version::{Variant, Version};
 
 /// PCB Artists SPL Module I2C default address.
 const DEVICE_ADDR_DEFAULT: u8 = 0x48;
@@ -13,25 +56,6 @@ const REG_CONTROL: u8 = 0x06;
 /// CONTROL register default value.
 pub const REG_CONTROL_DEFAULT: u8 = 0x02;
 
-#[cfg(not(feature = "external_mic"))]
-#[bitfield(u8)]
-#[derive(PartialEq, Eq, Format)]
-pub struct ControlRegister {
-    /// Set to power down the sensor
-    power_down: bool,
-    /// Filter selection
-    #[bits(2)]
-    filter_setting: FilterSetting,
-    /// Set to enable interrupt pin operation
-    interrupt_enable: bool,
-    /// Set to enable min/max level interrupts
-    interrupt_type: bool,
-    /// Padding
-    #[bits(3)]
-    __: u8,
-}
-
-#[cfg(feature = "external_mic")]
 #[bitfield(u8)]
 #[derive(PartialEq, Eq, Format)]
 pub struct ControlRegister {
@@ -51,7 +75,12 @@ pub struct ControlRegister {
     __: u8,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Mask of the reserved bits `[7:6]` of the CONTROL register.
+const CONTROL_RESERVED_MASK: u8 = 0b1100_0000;
+/// Mask of the filter setting bits `[2:1]` of the CONTROL register.
+const CONTROL_FILTER_MASK: u8 = 0b0000_0110;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Format)]
 pub enum FilterSetting {
     /// No filter
     None = 0b00,
@@ -59,14 +88,17 @@ pub enum FilterSetting {
     AWeighting = 0b01,
     /// C-weighting
     CWeighting = 0b10,
+    /// Reserved encoding, never written to the module
+    Reserved = 0b11,
 }
 
 impl FilterSetting {
     const fn from_bits(bits: u8) -> Self {
-        match bits {
+        match bits & 0b11 {
             0b00 => Self::None,
             0b01 => Self::AWeighting,
-            _ => Self::CWeighting,
+            0b10 => Self::CWeighting,
+            _ => Self::Reserved,
         }
     }
 
@@ -83,6 +115,76 @@ impl ControlRegister {
     pub fn set_filter(&mut self, filter_setting: FilterSetting) {
         self.set_filter_setting(filter_setting);
     }
+
+    /// Returns the filter setting.
+    ///
+    /// Returns [`FilterSetting::Reserved`] if the filter setting bits hold the
+    /// reserved encoding.
+    ///
+    pub fn filter(&self) -> FilterSetting {
+        self.filter_setting()
+    }
+
+    /// Returns true if the register holds the reserved filter setting or has
+    /// any of the reserved bits `[7:6]` set.
+    ///
+    /// The driver refuses to write such a value back to the module.
+    ///
+    pub const fn has_reserved_bits(&self) -> bool {
+        has_reserved_bits(REG_CONTROL, self.into_bits())
+    }
+
+    /// Sets the threshold interrupt mode
+    ///
+    /// Sets both the Interrupt Type and Interrupt Enable bits so the INT pin
+    /// is asserted when a reading falls outside of the THR_MIN and THR_MAX
+    /// window, or clears both bits to disable the interrupt.
+    ///
+    pub fn set_threshold_interrupt(&mut self, enable: bool) {
+        self.set_interrupt_type(enable);
+        self.set_interrupt_enable(enable);
+    }
+
+    /// Returns true if both the Interrupt Type and Interrupt Enable bits are set.
+    ///
+    pub fn threshold_interrupt(&self) -> bool {
+        self.interrupt_type() && self.interrupt_enable()
+    }
+
+    /// Sets the interrupt mode
+    ///
+    /// Sets the Interrupt Enable and Interrupt Type bits for the source of the
+    /// interrupt on the INT pin.
+    ///
+    pub fn set_interrupt_mode(&mut self, mode: InterruptMode) {
+        self.set_interrupt_enable(mode != InterruptMode::Disabled);
+        self.set_interrupt_type(mode == InterruptMode::Threshold);
+    }
+
+    /// Returns the interrupt mode set by the Interrupt Enable and Interrupt
+    /// Type bits.
+    ///
+    pub fn interrupt_mode(&self) -> InterruptMode {
+        match (self.interrupt_enable(), self.interrupt_type()) {
+            (false, _) => InterruptMode::Disabled,
+            (true, false) => InterruptMode::History,
+            (true, true) => InterruptMode::Threshold,
+        }
+    }
+
+    /// Sets the Enable Line Output bit
+    ///
+    /// Line output is only available on modules with an external microphone.
+    ///
+    pub fn set_line_out(&mut self, enable: bool) {
+        self.set_enable_line_out(enable);
+    }
+
+    /// Returns true if the Enable Line Output bit is set.
+    ///
+    pub fn line_out(&self) -> bool {
+        self.enable_line_out()
+    }
 }
 
 /// RESET register address.
@@ -94,19 +196,22 @@ pub const REG_RESET_DEFAULT: u8 = 0x00;
 #[derive(PartialEq, Eq, Format)]
 pub struct ResetRegister {
     /// Set this bit to clear interrupt signal and set INT pin to high-Z; this bit is self-clearing
-    clear_interrupt: bool,
-    /// Set this bit to clear the max and min dB values stored in MAX and MIN registers; t his bit is self-clearing
-    clear_min_max: bool,
+    pub clear_interrupt: bool,
+    /// Set this bit to clear the max and min dB values stored in MAX and MIN registers; this bit is self-clearing
+    pub clear_min_max: bool,
     /// Set this bit to clear the most recent 100 decibel values stored in history registers; this bit is self-clearing.
-    clear_history: bool,
+    pub clear_history: bool,
     /// Set this bit to perform a soft system reset and restore settings to defaults; this bit is self-clearing.
     /// NOTE: This bit must be set to wake up the device from sleep mode.
-    system_reset: bool,
+    pub system_reset: bool,
     /// Padding for reserved bits
     #[bits(4)]
     __: u8,
 }
 
+/// Mask of the reserved bits `[7:4]` of the RESET register.
+const RESET_RESERVED_MASK: u8 = 0b1111_0000;
+
 /// VESION register address.
 const REG_VERSION: u8 = 0x00;
 /// DECIBEL register address.
@@ -116,55 +221,228 @@ const REGS_DEVICE_ID: [u8; 4] = [0x01, 0x02, 0x03, 0x04];
 /// MAX register.
 const REG_MAX: u8 = 0x0c;
 /// MIN register.
-const REG_MIN: u8 = 0x0d;
+const REG_MIN: u8 = 0x0b;
+/// THR_MIN register address.
+const REG_THR_MIN: u8 = 0x0d;
+/// THR_MIN register default value in decibels.
+pub const REG_THR_MIN_DEFAULT: u8 = 45;
+/// THR_MAX register address.
+const REG_THR_MAX: u8 = 0x0e;
+/// THR_MAX register default value in decibels.
+pub const REG_THR_MAX_DEFAULT: u8 = 85;
 /// SCRATCH register address.
 const REG_SCRATCH: u8 = 0x05;
+/// SCRATCH register default value.
+pub const REG_SCRATCH_DEFAULT: u8 = 0xaa;
 /// TAVG register high byte address.
 const REG_TAVG_HIGH: u8 = 0x07;
+/// TAVG register low byte address.
+const REG_TAVG_LOW: u8 = 0x08;
 /// Default value for averaging time in ms.
 pub const REG_AVERAGING_TIME_DEFAULT_MS: u16 = 1000;
 
 /// GAIN register.
-#[cfg(feature = "external_mic")]
 const REG_GAIN: u8 = 0x0f;
+/// GAIN register default value in 0.5 dB steps.
+pub const REG_GAIN_DEFAULT: u8 = 18;
+
+/// DBHISTORY_0 register address, the latest of the 100 history registers.
+const REG_DBHISTORY_0: u8 = 0x14;
+/// DBHISTORY_99 register address, the oldest of the 100 history registers.
+const REG_DBHISTORY_99: u8 = REG_DBHISTORY_0 + (HISTORY_LEN as u8 - 1);
+
+/// FREQ_64BINS_0 register address, the lowest of the 64 frequency bins.
+const REG_FREQ_64BINS_0: u8 = 0x78;
+/// FREQ_64BINS_63 register address, the highest of the 64 frequency bins.
+const REG_FREQ_64BINS_63: u8 = REG_FREQ_64BINS_0 + (SPECTRUM_BINS as u8 - 1);
+
+/// Register access type as published in the vendor programming manual.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    /// Read-only (R/O).
+    ReadOnly,
+    /// Write-only (W/O).
+    WriteOnly,
+    /// Read/write (R/W).
+    ReadWrite,
+}
+
+impl Access {
+    const fn is_readable(self) -> bool {
+        matches!(self, Self::ReadOnly | Self::ReadWrite)
+    }
+
+    const fn is_writable(self) -> bool {
+        matches!(self, Self::WriteOnly | Self::ReadWrite)
+    }
+}
+
+/// Gets the access type of a register.
+///
+/// Returns `None` for undocumented locations, which the vendor reserves for
+/// diagnostics and calibration.
+///
+const fn register_access(reg: u8) -> Option<Access> {
+    match reg {
+        REG_VERSION | REG_DECIBEL | REG_MIN | REG_MAX => Some(Access::ReadOnly),
+        r if r >= REGS_DEVICE_ID[0] && r <= REGS_DEVICE_ID[3] => Some(Access::ReadOnly),
+        REG_SCRATCH | REG_CONTROL | REG_TAVG_HIGH | REG_TAVG_LOW => Some(Access::ReadWrite),
+        REG_THR_MIN | REG_THR_MAX | REG_GAIN => Some(Access::ReadWrite),
+        REG_RESET => Some(Access::WriteOnly),
+        REG_DBHISTORY_0..=REG_DBHISTORY_99 => Some(Access::ReadOnly),
+        REG_FREQ_64BINS_0..=REG_FREQ_64BINS_63 => Some(Access::ReadOnly),
+        _ => None,
+    }
+}
+
+/// Returns true if `len` consecutive registers starting at `start_reg` are
+/// documented and readable.
+///
+fn is_readable_range(start_reg: u8, len: usize) -> bool {
+    (0..len).all(|offset| {
+        let reg = start_reg as usize + offset;
+        reg <= u8::MAX as usize
+            && matches!(register_access(reg as u8), Some(access) if access.is_readable())
+    })
+}
+
+/// Returns true if `len` consecutive registers starting at `start_reg` are
+/// documented and writable.
+///
+fn is_writable_range(start_reg: u8, len: usize) -> bool {
+    (0..len).all(|offset| {
+        let reg = start_reg as usize + offset;
+        reg <= u8::MAX as usize
+            && matches!(register_access(reg as u8), Some(access) if access.is_writable())
+    })
+}
+
+/// Gets the bits of a register compared when verifying a write, or `None` if
+/// writes to the register are not verified.
+///
+/// Only the configuration registers are verified. The reserved bits of
+/// CONTROL are not compared, since the module may read them back as either
+/// value.
+///
+const fn verify_mask(reg: u8) -> Option<u8> {
+    match reg {
+        REG_CONTROL => Some(!CONTROL_RESERVED_MASK),
+        REG_TAVG_HIGH | REG_TAVG_LOW | REG_THR_MIN | REG_THR_MAX | REG_GAIN => Some(0xff),
+        _ => None,
+    }
+}
+
+/// Returns true if writing `value` to a register would set one of its
+/// reserved bits or write a reserved encoding.
+///
+/// The manual requires the reserved bits of CONTROL and RESET to be written
+/// as 0, and the filter setting 0b11 is reserved.
+///
+const fn has_reserved_bits(reg: u8, value: u8) -> bool {
+    match reg {
+        REG_CONTROL => {
+            value & CONTROL_RESERVED_MASK != 0 || value & CONTROL_FILTER_MASK == CONTROL_FILTER_MASK
+        }
+        REG_RESET => value & RESET_RESERVED_MASK != 0,
+        _ => false,
+    }
+}
+
+/// Compares the bytes `written` from a starting register with the bytes
+/// `read` back, ignoring the bits masked out by [`verify_mask`].
+///
+fn compare_written<E>(start_reg: u8, written: &[u8], read: &[u8]) -> Result<(), Error<E>> {
+    for (reg, (&written, &read)) in (start_reg..).zip(written.iter().zip(read.iter())) {
+        let mask = verify_mask(reg).unwrap_or(0xff);
+        if (written ^ read) & mask != 0 {
+            return Err(Error::VerifyFailed {
+                register: reg,
+                written,
+                read,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Writes the complement of the SCRATCH register of the device at `address`,
+/// reads it back, and restores the original value.
+///
+/// Returns true if the value read back matches the value written. The original
+/// value is restored even if reading it back failed, and an error reading it
+/// back is returned before an error restoring it.
+///
+fn verify_scratch<I2C>(i2c: &mut I2C, address: u8) -> Result<bool, Error<I2C::Error>>
+where
+    I2C: I2cBus,
+{
+    let mut original = [0; 1];
+    i2c.write_read(address, &[REG_SCRATCH], &mut original)
+        .map_err(Error::i2c(REG_SCRATCH, Operation::Read))?;
+
+    let pattern = !original[0];
+    i2c.write(address, &[REG_SCRATCH, pattern])
+        .map_err(Error::i2c(REG_SCRATCH, Operation::Write))?;
+    let mut read_back = [0; 1];
+    let read_back_result = i2c.write_read(address, &[REG_SCRATCH], &mut read_back);
+    let restore_result = i2c.write(address, &[REG_SCRATCH, original[0]]);
+    read_back_result.map_err(Error::i2c(REG_SCRATCH, Operation::Read))?;
+    restore_result.map_err(Error::i2c(REG_SCRATCH, Operation::Write))?;
+
+    Ok(read_back[0] == pattern)
+}
 
 /// A PA SPL Module on the I2C bus `I2C`.
+///
+/// The driver does not need to own the bus. With embedded-hal 1.0 it accepts
+/// a borrowed bus (`&mut I2C`) or a shared bus device from
+/// [embedded-hal-bus](https://crates.io/crates/embedded-hal-bus), such as
+/// `RefCellDevice`, `CriticalSectionDevice` or `MutexDevice`, so it can share
+/// the bus with other sensors.
 pub struct PaSpl<I2C>
 where
-    I2C: i2c::Read + i2c::Write + i2c::WriteRead,
+    I2C: I2cBus,
 {
-    i2c: Option<I2C>,
+    i2c: I2C,
     device_addr: u8,
-}
-
-/// A driver error.
-#[derive(Debug, PartialEq, Eq)]
-pub enum Error<E> {
-    /// I2C bus error.
-    I2c(E),
-    /// No I2C instance available.
-    NoI2cInstance,
-    /// Buffer overflow.
-    BufferOverflow,
+    /// VERSION register value, cached on first use.
+    version: Option<Version>,
+    /// Read back and compare writes to configuration registers.
+    verify_writes: bool,
 }
 
 impl<E, I2C> PaSpl<I2C>
 where
-    I2C: i2c::Read<Error = E> + i2c::Write<Error = E> + i2c::WriteRead<Error = E>,
+    I2C: I2cBus<Error = E>,
 {
     /// Initializes the PCB Artists SPL Module driver.
     ///
-    /// # Errors
-    ///
-    /// Returns [`Error::NoI2cInstance`] if the I2C instance is empty.
-    ///
     pub fn new(i2c: I2C) -> Self {
         Self {
-            i2c: Some(i2c),
+            i2c,
             device_addr: DEVICE_ADDR_DEFAULT,
+            version: None,
+            verify_writes: false,
         }
     }
 
+    /// Enables or disables verification of configuration writes.
+    ///
+    /// When enabled, every write to CONTROL, TAVG, THR_MIN, THR_MAX and GAIN
+    /// is read back and compared with the value written, and a mismatch
+    /// returns [`Error::VerifyFailed`]. The reserved bits of CONTROL are not
+    /// compared. Verification is disabled by default.
+    ///
+    pub fn set_verify_writes(&mut self, enable: bool) {
+        self.verify_writes = enable;
+    }
+
+    /// Returns true if configuration writes are verified.
+    ///
+    pub fn verify_writes(&self) -> bool {
+        self.verify_writes
+    }
+
     /// Sets a new I2C device address.
     ///
     /// The published device address is the default but the vendor's website
@@ -172,32 +450,76 @@ where
     ///
     pub fn set_device_addr(&mut self, addr: u8) {
         self.device_addr = addr;
+        self.version = None;
+    }
+
+    /// Clears the history registers.
+    ///
+    /// Clears the 100 most recent decibel values stored in DBHISTORY_0 to
+    /// DBHISTORY_99 by setting the Clear History bit in the RESET register.
+    /// The configuration of the module is not changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn clear_history(&mut self) -> Result<(), Error<E>> {
+        self.set_reset_register(ResetRegister::new().with_clear_history(true))
+    }
+
+    /// Clears a pending interrupt.
+    ///
+    /// Clears the interrupt signal and sets the INT pin to high-Z by setting
+    /// the Clear Interrupt bit in the RESET register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn clear_interrupt(&mut self) -> Result<(), Error<E>> {
+        self.set_reset_register(ResetRegister::new().with_clear_interrupt(true))
+    }
+
+    /// Clears the MIN and MAX registers.
+    ///
+    /// Clears the minimum and maximum decibel values captured since power-up
+    /// or the last clear by setting the Clear MIN/MAX bit in the RESET
+    /// register. The configuration of the module is not changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn clear_min_max(&mut self) -> Result<(), Error<E>> {
+        self.set_reset_register(ResetRegister::new().with_clear_min_max(true))
     }
 
-    /// Gets the 16-bit averaging time in ms from registers TAVG high and TAVG low (0x07 and 0x08).
+    /// Gets the averaging time from registers TAVG high and TAVG low (0x07 and 0x08).
     ///
     /// # Errors
     ///
-    /// Returns [`Error::NoI2cInstance`] if the I2C instance is empty.
+    /// Returns [`Error::InvalidRegisterValue`] if the registers hold less than
+    /// 10 ms or more than 10,000 ms.
     ///
     /// Returns [`Error::I2c`] if I2C returns an error.
     ///
-    pub fn get_avg_time(&mut self) -> Result<u16, Error<E>> {
+    pub fn get_avg_time(&mut self) -> Result<AveragingTime, Error<E>> {
         let mut buffer: [u8; 2] = [0; 2];
         self.read_bytes(REG_TAVG_HIGH, &mut buffer)?;
 
         // Combine the bytes into a u16.
         let avg_time_ms = ((buffer[0] as u16) << 8) | (buffer[1] as u16);
 
-        Ok(avg_time_ms)
+        AveragingTime::from_ms(avg_time_ms).ok_or(Error::InvalidRegisterValue {
+            register: REG_TAVG_HIGH,
+            value: avg_time_ms,
+        })
     }
 
     /// Gets the CONTROL register.
     ///
     /// # Errors
     ///
-    /// Returns [`Error::NoI2cInstance`] if the I2C instance is empty.
-    ///
     /// Returns [`Error::I2c`] if I2C returns an error.
     ///
     pub fn get_control_register(&mut self) -> Result<ControlRegister, Error<E>> {
@@ -209,8 +531,6 @@ where
     ///
     /// # Errors
     ///
-    /// Returns [`Error::NoI2cInstance`] if the I2C instance is empty.
-    ///
     /// Returns [`Error::I2c`] if I2C returns an error.
     ///
     pub fn get_device_id(&mut self) -> Result<u32, Error<E>> {
@@ -228,34 +548,124 @@ where
 
     /// Gets the firmware version from the VERSION register.
     ///
-    /// # Errors
+    /// This is the raw register value; use [`PaSpl::get_version`] to decode
+    /// the hardware version, firmware version, and product variant.
     ///
-    /// Returns [`Error::NoI2cInstance`] if the I2C instance is empty.
+    /// # Errors
     ///
     /// Returns [`Error::I2c`] if I2C returns an error.
     ///
     pub fn get_firmware_version(&mut self) -> Result<u8, Error<E>> {
-        self.read_byte(REG_VERSION)
+        self.get_version().map(Version::into_bits)
+    }
+
+    /// Gets the hardware and firmware version from the VERSION register.
+    ///
+    /// The version identifies the product [`Variant`] of the module and the
+    /// features it supports. The version is cached for use by the driver.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn get_version(&mut self) -> Result<Version, Error<E>> {
+        let version = Version::from_bits(self.read_byte(REG_VERSION)?);
+        self.version = Some(version);
+        Ok(version)
+    }
+
+    /// Gets the product variant of the module.
+    ///
+    /// The variant is decoded from the VERSION register, which is only read
+    /// the first time the version is needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn get_variant(&mut self) -> Result<Variant, Error<E>> {
+        match self.version {
+            Some(version) => Ok(version.variant()),
+            None => self.get_version().map(Version::variant),
+        }
     }
 
-    /// Gets the gain value in 0.5 decibel steps from the GAIN register.
+    /// Gets the microphone gain from the GAIN register.
     ///
     /// This value only needs to be modified if you are using your own
     /// microphone. The default value will work with the default microphone
     /// supplied with the module.
     ///
-    /// Acceptable values are 0 to 95 to set the gain in 0.5 dB steps (+0.0 dB
-    /// to +47.5 dB).
+    /// Gain is only available on modules with an external microphone.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Unsupported`] if the variant has no adjustable gain.
+    ///
+    /// Returns [`Error::InvalidRegisterValue`] if the register holds more than
+    /// 95 steps.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn get_gain(&mut self) -> Result<Gain, Error<E>> {
+        self.require(Variant::has_gain)?;
+        let steps = self.read_byte(REG_GAIN)?;
+        Gain::from_steps(steps).ok_or(Error::InvalidRegisterValue {
+            register: REG_GAIN,
+            value: steps as u16,
+        })
+    }
+
+    /// Gets the state of the line output from the CONTROL register.
+    ///
+    /// Line output is only available on modules with an external microphone.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Unsupported`] if the variant has no line output.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn get_line_out(&mut self) -> Result<bool, Error<E>> {
+        self.require(Variant::has_line_out)?;
+        Ok(self.get_control_register()?.line_out())
+    }
+
+    /// Gets the decibel history from registers DBHISTORY_0 to DBHISTORY_99
+    /// (0x14 to 0x77).
+    ///
+    /// All 100 entries are read in a single burst read and are ordered newest
+    /// first. A new entry is captured every Tavg period.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn read_history(&mut self) -> Result<History, Error<E>> {
+        self.read_history_range(0, HISTORY_LEN)
+    }
+
+    /// Gets `len` entries of the decibel history starting at DBHISTORY_`start`.
+    ///
+    /// The entries are read in a single burst read and are ordered newest
+    /// first, so `read_history_range(0, 10)` returns the 10 latest readings.
     ///
     /// # Errors
     ///
-    /// Returns [`Error::NoI2cInstance`] if the I2C instance is empty.
+    /// Returns [`Error::InvalidArgument`] if `len` is 0 or the range extends
+    /// past DBHISTORY_99.
     ///
     /// Returns [`Error::I2c`] if I2C returns an error.
     ///
-    #[cfg(feature = "external_mic")]
-    pub fn get_gain(&mut self) -> Result<u8, Error<E>> {
-        self.read_byte(REG_GAIN)
+    pub fn read_history_range(&mut self, start: usize, len: usize) -> Result<History, Error<E>> {
+        if len == 0 || start.saturating_add(len) > HISTORY_LEN {
+            return Err(Error::InvalidArgument);
+        }
+
+        let mut buffer: [u8; HISTORY_LEN] = [0; HISTORY_LEN];
+        self.read_bytes(REG_DBHISTORY_0 + start as u8, &mut buffer[..len])?;
+
+        Ok(History::from_slice(start, &buffer[..len]))
     }
 
     /// Gets the latest SPL value in decibels from the DECIBEL register.
@@ -265,8 +675,6 @@ where
     ///
     /// # Errors
     ///
-    /// Returns [`Error::NoI2cInstance`] if the I2C instance is empty.
-    ///
     /// Returns [`Error::I2c`] if I2C returns an error.
     ///
     pub fn get_latest_decibel(&mut self) -> Result<u8, Error<E>> {
@@ -279,8 +687,6 @@ where
     ///
     /// # Errors
     ///
-    /// Returns [`Error::NoI2cInstance`] if the I2C instance is empty.
-    ///
     /// Returns [`Error::I2c`] if I2C returns an error.
     ///
     pub fn get_max_decibel(&mut self) -> Result<u8, Error<E>> {
@@ -293,19 +699,37 @@ where
     ///
     /// # Errors
     ///
-    /// Returns [`Error::NoI2cInstance`] if the I2C instance is empty.
-    ///
     /// Returns [`Error::I2c`] if I2C returns an error.
     ///
     pub fn get_min_decibel(&mut self) -> Result<u8, Error<E>> {
         self.read_byte(REG_MIN)
     }
 
-    /// Gets the value stored in the SCRATCH register.
+    /// Gets the audio spectrum from registers FREQ_64BINS_0 to FREQ_64BINS_63
+    /// (0x78 to 0xB7).
+    ///
+    /// All 64 bins are read in a single burst read. The spectrum analyzer is
+    /// only available on the spectrum analyzer variant of the module, which is
+    /// detected from the VERSION register the first time this is called.
     ///
     /// # Errors
     ///
-    /// Returns [`Error::NoI2cInstance`] if the I2C instance is empty.
+    /// Returns [`Error::Unsupported`] if the variant has no spectrum analyzer.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn read_spectrum(&mut self) -> Result<Spectrum, Error<E>> {
+        self.require(Variant::has_spectrum)?;
+
+        let mut buffer: [u8; SPECTRUM_BINS] = [0; SPECTRUM_BINS];
+        self.read_bytes(REG_FREQ_64BINS_0, &mut buffer)?;
+
+        Ok(Spectrum::from_bins(buffer))
+    }
+
+    /// Gets the value stored in the SCRATCH register.
+    ///
+    /// # Errors
     ///
     /// Returns [`Error::I2c`] if I2C returns an error.
     ///
@@ -313,31 +737,60 @@ where
         self.read_byte(REG_SCRATCH)
     }
 
+    /// Gets the lower interrupt threshold in decibels from the THR_MIN register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn get_threshold_min(&mut self) -> Result<u8, Error<E>> {
+        self.read_byte(REG_THR_MIN)
+    }
+
+    /// Gets the upper interrupt threshold in decibels from the THR_MAX register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn get_threshold_max(&mut self) -> Result<u8, Error<E>> {
+        self.read_byte(REG_THR_MAX)
+    }
+
+    /// Gets the interrupt threshold window in decibels as `(min, max)` from
+    /// the THR_MIN and THR_MAX registers.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn get_threshold_window(&mut self) -> Result<(u8, u8), Error<E>> {
+        let mut buffer: [u8; 2] = [0; 2];
+        self.read_bytes(REG_THR_MIN, &mut buffer)?;
+        Ok((buffer[0], buffer[1]))
+    }
+
     /// Soft resets the sensor.
     ///
     /// The sensor is soft reset by setting the System Reset bit in the RESET register.
     ///
     /// # Errors
     ///
-    /// Returns [`Error::NoI2cInstance`] if the I2C instance is empty.
-    ///
     /// Returns [`Error::I2c`] if I2C returns an error.
     ///
     pub fn reset(&mut self) -> Result<(), Error<E>> {
-        let reg_reset = ResetRegister::new().with_system_reset(true);
-        self.write_byte(REG_RESET, reg_reset.into_bits())
+        self.set_reset_register(ResetRegister::new().with_system_reset(true))
     }
 
-    /// Sets the average time in ms for calculating SPL.
+    /// Sets the averaging time for calculating SPL.
     ///
     /// # Errors
     ///
-    /// Returns [`Error::NoI2cInstance`] if the I2C instance is empty.
-    ///
     /// Returns [`Error::I2c`] if I2C returns an error.
     ///
-    pub fn set_avg_time(&mut self, ms: u16) -> Result<(), Error<E>> {
+    pub fn set_avg_time(&mut self, averaging_time: AveragingTime) -> Result<(), Error<E>> {
         // Convert the average time in ms to high and low bytes.
+        let ms = averaging_time.ms();
         let tavg_high_byte: u8 = (ms >> 8) as u8;
         let tavg_low_byte: u8 = (ms & 0xFF) as u8;
         let buffer = [tavg_high_byte, tavg_low_byte];
@@ -349,94 +802,250 @@ where
     ///
     /// # Errors
     ///
-    /// Returns [`Error::NoI2cInstance`] if the I2C instance is empty.
+    /// Returns [`Error::Unsupported`] if the Enable Line Output bit is set and
+    /// the variant has no line output.
+    ///
+    /// Returns [`Error::ReservedBits`] if `reg` has reserved bits set or holds
+    /// the reserved filter setting.
     ///
     /// Returns [`Error::I2c`] if I2C returns an error.
     ///
     pub fn set_control_register(&mut self, reg: ControlRegister) -> Result<(), Error<E>> {
+        if reg.line_out() {
+            self.require(Variant::has_line_out)?;
+        }
+
         self.write_byte(REG_CONTROL, reg.into_bits())
     }
 
-    /// Sets the gain in the GAIN register.
+    /// Sets the microphone gain in the GAIN register.
+    ///
+    /// Gain is only available on modules with an external microphone.
     ///
     /// # Errors
     ///
-    /// Returns [`Error::NoI2cInstance`] if the I2C instance is empty.
+    /// Returns [`Error::Unsupported`] if the variant has no adjustable gain.
     ///
     /// Returns [`Error::I2c`] if I2C returns an error.
-    /// ```
-    #[cfg(feature = "external_mic")]
-    pub fn set_gain(&mut self, value: u8) -> Result<(), Error<E>> {
-        self.write_byte(REG_GAIN, value)
+    ///
+    pub fn set_gain(&mut self, gain: Gain) -> Result<(), Error<E>> {
+        self.require(Variant::has_gain)?;
+        self.write_byte(REG_GAIN, gain.steps())
     }
 
-    /// Sets the value stored in the SCRATCH register.
+    /// Enables or disables the line output.
+    ///
+    /// Sets the Enable Line Output bit of the CONTROL register with a
+    /// read-modify-write. Line output is derived directly from the microphone
+    /// with the gain set in the GAIN register applied.
     ///
     /// # Errors
     ///
-    /// Returns [`Error::NoI2cInstance`] if the I2C instance is empty.
+    /// Returns [`Error::Unsupported`] if the variant has no line output.
     ///
     /// Returns [`Error::I2c`] if I2C returns an error.
     ///
-    pub fn set_scratch(&mut self, value: u8) -> Result<(), Error<E>> {
-        self.write_byte(REG_SCRATCH, value)
+    pub fn set_line_out(&mut self, enable: bool) -> Result<(), Error<E>> {
+        self.require(Variant::has_line_out)?;
+        let mut reg_control = self.get_control_register()?;
+        reg_control.set_line_out(enable);
+        self.write_byte(REG_CONTROL, reg_control.into_bits())
     }
 
-    /// Destroys this driver and releases the I2C bus.
+    /// Sets the lower interrupt threshold in decibels in the THR_MIN register.
     ///
-    pub fn destroy(&mut self) -> I2C {
-        self.i2c
-            .take()
-            .expect("I2C instance has already been taken")
-    }
-
-    /// Reads a single byte from an I2C register of the device.
+    /// The interrupt is asserted when a reading falls below this value while
+    /// threshold interrupts are enabled.
     ///
-    fn read_byte(&mut self, reg: u8) -> Result<u8, Error<E>> {
-        let mut buffer = [0; 1];
-        self.i2c
-            .as_mut()
-            .ok_or(Error::NoI2cInstance)?
-            .write_read(self.device_addr, &[reg], &mut buffer)
-            .map_err(Error::I2c)?;
-        Ok(buffer[0])
-    }
-
-    /// Read multiple bytes from a starting register.
+    /// # Errors
     ///
-    fn read_bytes(&mut self, start_reg: u8, buffer: &mut [u8]) -> Result<(), Error<E>> {
-        self.i2c
-            .as_mut()
-            .ok_or(Error::NoI2cInstance)?
-            .write_read(self.device_addr, &[start_reg], buffer)
-            .map_err(Error::I2c)?;
-        Ok(())
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn set_threshold_min(&mut self, db: u8) -> Result<(), Error<E>> {
+        self.write_byte(REG_THR_MIN, db)
     }
 
-    /// Writes a single byte to an I2C register of the device.
+    /// Sets the upper interrupt threshold in decibels in the THR_MAX register.
     ///
-    fn write_byte(&mut self, reg: u8, value: u8) -> Result<(), Error<E>> {
-        self.i2c
-            .as_mut()
-            .ok_or(Error::NoI2cInstance)?
-            .write(self.device_addr, &[reg, value])
-            .map_err(Error::I2c)
+    /// The interrupt is asserted when a reading rises above this value while
+    /// threshold interrupts are enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn set_threshold_max(&mut self, db: u8) -> Result<(), Error<E>> {
+        self.write_byte(REG_THR_MAX, db)
     }
 
-    /// Writes two bytes from a starting register.
+    /// Sets the interrupt threshold window in decibels in the THR_MIN and
+    /// THR_MAX registers with a single write.
     ///
-    fn write_two_bytes(&mut self, reg: u8, buffer: &[u8]) -> Result<(), Error<E>> {
-        if buffer.len() > 2 {
-            return Err(Error::BufferOverflow);
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidArgument`] if `min` is greater than `max`.
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn set_threshold_window(&mut self, min: u8, max: u8) -> Result<(), Error<E>> {
+        if min > max {
+            return Err(Error::InvalidArgument);
         }
 
-        self.i2c
-            .as_mut()
-            .ok_or(Error::NoI2cInstance)?
-            .write(self.device_addr, &[reg, buffer[0], buffer[1]])
-            .map_err(Error::I2c)
+        self.write_two_bytes(REG_THR_MIN, &[min, max])
     }
-}
+
+    /// Enables the threshold interrupt.
+    ///
+    /// Sets the Interrupt Type and Interrupt Enable bits of the CONTROL
+    /// register with a read-modify-write so the INT pin is pulled low when a
+    /// reading leaves the window set by THR_MIN and THR_MAX.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn enable_threshold_interrupt(&mut self) -> Result<(), Error<E>> {
+        let mut reg_control = self.get_control_register()?;
+        reg_control.set_threshold_interrupt(true);
+        self.set_control_register(reg_control)
+    }
+
+    /// Disables the threshold interrupt.
+    ///
+    /// Clears the Interrupt Type and Interrupt Enable bits of the CONTROL
+    /// register with a read-modify-write.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn disable_threshold_interrupt(&mut self) -> Result<(), Error<E>> {
+        let mut reg_control = self.get_control_register()?;
+        reg_control.set_threshold_interrupt(false);
+        self.set_control_register(reg_control)
+    }
+
+    /// Sets the RESET register.
+    ///
+    /// Performs every operation whose bit is set in `reg` with a single write,
+    /// e.g. clearing MIN/MAX and the history together at the start of an
+    /// exposure period. All bits of the RESET register are self-clearing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn set_reset_register(&mut self, reg: ResetRegister) -> Result<(), Error<E>> {
+        self.write_byte(REG_RESET, reg.into_bits())
+    }
+
+    /// Sets the value stored in the SCRATCH register.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error.
+    ///
+    pub fn set_scratch(&mut self, value: u8) -> Result<(), Error<E>> {
+        self.write_byte(REG_SCRATCH, value)
+    }
+
+    /// Destroys this driver and releases the I2C bus.
+    ///
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    /// Checks that the variant of the module supports a feature.
+    ///
+    fn require(&mut self, supported: fn(Variant) -> bool) -> Result<(), Error<E>> {
+        let variant = self.get_variant()?;
+        if supported(variant) {
+            Ok(())
+        } else {
+            Err(Error::Unsupported(variant))
+        }
+    }
+
+    /// Reads a single byte from an I2C register of the device.
+    ///
+    fn read_byte(&mut self, reg: u8) -> Result<u8, Error<E>> {
+        debug_assert!(is_readable_range(reg, 1));
+        let mut buffer = [0; 1];
+        self.i2c
+            .write_read(self.device_addr, &[reg], &mut buffer)
+            .map_err(Error::i2c(reg, Operation::Read))?;
+        Ok(buffer[0])
+    }
+
+    /// Read multiple bytes from a starting register.
+    ///
+    fn read_bytes(&mut self, start_reg: u8, buffer: &mut [u8]) -> Result<(), Error<E>> {
+        debug_assert!(is_readable_range(start_reg, buffer.len()));
+        self.i2c
+            .read_registers(self.device_addr, start_reg, buffer)
+            .map_err(Error::i2c(start_reg, Operation::Read))?;
+        Ok(())
+    }
+
+    /// Writes a single byte to an I2C register of the device.
+    ///
+    /// Nothing is written if `value` sets reserved bits of the register.
+    ///
+    fn write_byte(&mut self, reg: u8, value: u8) -> Result<(), Error<E>> {
+        debug_assert!(is_writable_range(reg, 1));
+        if has_reserved_bits(reg, value) {
+            return Err(Error::ReservedBits {
+                register: reg,
+                value,
+            });
+        }
+        self.i2c
+            .write(self.device_addr, &[reg, value])
+            .map_err(Error::i2c(reg, Operation::Write))?;
+        self.verify_write(reg, &[value])
+    }
+
+    /// Writes two bytes from a starting register.
+    ///
+    fn write_two_bytes(&mut self, reg: u8, buffer: &[u8]) -> Result<(), Error<E>> {
+        if buffer.len() > 2 {
+            return Err(Error::BufferOverflow);
+        }
+        debug_assert!(is_writable_range(reg, 2));
+
+        self.i2c
+            .write(self.device_addr, &[reg, buffer[0], buffer[1]])
+            .map_err(Error::i2c(reg, Operation::Write))?;
+        self.verify_write(reg, buffer)
+    }
+
+    /// Reads back the bytes `written` from a starting register and compares
+    /// them, if writes are verified.
+    ///
+    fn verify_write(&mut self, start_reg: u8, written: &[u8]) -> Result<(), Error<E>> {
+        if !self.verify_writes || verify_mask(start_reg).is_none() {
+            return Ok(());
+        }
+
+        let mut buffer = [0; 2];
+        let read = &mut buffer[..written.len()];
+        if let [value] = read {
+            *value = self.read_byte(start_reg)?;
+        } else {
+            self.read_bytes(start_reg, read)?;
+        }
+
+        compare_written(start_reg, written, read)
+    }
+}
+
+#[cfg(test)]
+mod conformance;
+#[cfg(test)]
+mod mock;
+#[cfg(all(test, feature = "eh1"))]
+mod shared_bus;
 
 #[cfg(test)]
 mod tests {
@@ -446,10 +1055,14 @@ mod tests {
     };
 
     use super::*;
-    use embedded_hal_mock::eh0::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+    use crate::mock::{read_registers, with_bus_error, I2cMock, I2cTransaction};
 
+    /// DEVICE_VER_MEMS_LTS: Published version for base features.
+    const DEVICE_VER_MEMS_LTS: u8 = 0x31;
     /// DEVICE_VER_MEMS_LTS: Published version for base features + audio spectrum analyzer.
     const DEVICE_VER_MEMS_LTS_ASA: u8 = 0x32;
+    /// DEVICE_VER_EXT_MIC: Published version for the external microphone module.
+    const DEVICE_VER_EXT_MIC: u8 = 0x81;
     /// TAVG register high byte default value.
     const REG_TAVG_HIGH_DEFAULT_BYTE: u8 = 0x03;
     /// TAVG register low byte default value.
@@ -465,24 +1078,24 @@ mod tests {
         pa_spl.set_device_addr(new_device_addr);
         assert_eq!(new_device_addr, pa_spl.device_addr);
 
-        let mut mock = pa_spl.destroy();
+        let mut mock = pa_spl.release();
         mock.done();
     }
 
     #[test]
     fn confirm_device_id() {
-        let expectations = vec![I2cTransaction::write_read(
+        let expectations = read_registers(
             DEVICE_ADDR_DEFAULT,
-            vec![REGS_DEVICE_ID[0]],
+            REGS_DEVICE_ID[0],
             vec![0x01, 0x02, 0x03, 0x04],
-        )];
+        );
         let i2c_mock = I2cMock::new(&expectations);
 
         let mut pa_spl = PaSpl::new(i2c_mock);
         let device_id = pa_spl.get_device_id().unwrap();
         assert_eq!(0x01020304, device_id);
 
-        let mut mock = pa_spl.destroy();
+        let mut mock = pa_spl.release();
         mock.done();
     }
 
@@ -499,24 +1112,94 @@ mod tests {
         let version = pa_spl.get_firmware_version().unwrap();
         assert_eq!(DEVICE_VER_MEMS_LTS_ASA, version);
 
-        let mut mock = pa_spl.destroy();
+        let mut mock = pa_spl.release();
         mock.done();
     }
 
     #[test]
-    fn confirm_get_avg_time() {
+    fn confirm_get_version() {
         let expectations = vec![I2cTransaction::write_read(
             DEVICE_ADDR_DEFAULT,
-            vec![REG_TAVG_HIGH],
-            vec![REG_TAVG_HIGH_DEFAULT_BYTE, REG_TAVG_LOW_DEFAULT_BYTE],
+            vec![REG_VERSION],
+            vec![DEVICE_VER_MEMS_LTS_ASA],
         )];
         let i2c_mock = I2cMock::new(&expectations);
         let mut pa_spl = PaSpl::new(i2c_mock);
 
-        let averaging_time_ms = pa_spl.get_avg_time().unwrap();
-        assert_eq!(REG_AVERAGING_TIME_DEFAULT_MS, averaging_time_ms);
+        let version = pa_spl.get_version().unwrap();
+        assert_eq!(0x3, version.hardware());
+        assert_eq!(0x2, version.firmware());
+        // The variant is decoded from the cached version.
+        assert_eq!(Variant::MemsSpectrumAnalyzer, pa_spl.get_variant().unwrap());
 
-        let mut mock = pa_spl.destroy();
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_version_variants() {
+        let cases = [
+            (0x31, Variant::MemsLts, false, false, false),
+            (0x32, Variant::MemsSpectrumAnalyzer, true, false, false),
+            (0x33, Variant::MemsSpectrumAnalyzer, true, false, false),
+            (0x81, Variant::ExternalMic, false, true, true),
+            (0x30, Variant::Unknown, false, false, false),
+            (0x34, Variant::Unknown, false, false, false),
+            (0x80, Variant::Unknown, false, false, false),
+            (0x82, Variant::Unknown, false, false, false),
+            (0x00, Variant::Unknown, false, false, false),
+            (0xff, Variant::Unknown, false, false, false),
+        ];
+
+        for (bits, variant, spectrum, gain, line_out) in cases {
+            let version = Version::from_bits(bits);
+            assert_eq!(variant, version.variant(), "{bits:#04x}");
+            assert_eq!(spectrum, version.has_spectrum(), "{bits:#04x}");
+            assert_eq!(gain, version.has_gain(), "{bits:#04x}");
+            assert_eq!(line_out, version.has_line_out(), "{bits:#04x}");
+            assert_eq!(bits, u8::from(version));
+        }
+
+        for bits in [0x30, 0x31, 0x3f, 0x80, 0x81, 0x8f] {
+            assert!(Version::from_bits(bits).is_known_hardware(), "{bits:#04x}");
+        }
+        for bits in [0x00, 0x21, 0x41, 0x71, 0x91, 0xff] {
+            assert!(!Version::from_bits(bits).is_known_hardware(), "{bits:#04x}");
+        }
+    }
+
+    #[test]
+    fn confirm_get_avg_time() {
+        let expectations = read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_TAVG_HIGH,
+            vec![REG_TAVG_HIGH_DEFAULT_BYTE, REG_TAVG_LOW_DEFAULT_BYTE],
+        );
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let averaging_time = pa_spl.get_avg_time().unwrap();
+        assert_eq!(REG_AVERAGING_TIME_DEFAULT_MS, averaging_time.ms());
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_get_avg_time_out_of_range() {
+        let expectations = read_registers(DEVICE_ADDR_DEFAULT, REG_TAVG_HIGH, vec![0x00, 0x00]);
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        assert_eq!(
+            Err(Error::InvalidRegisterValue {
+                register: REG_TAVG_HIGH,
+                value: 0,
+            }),
+            pa_spl.get_avg_time()
+        );
+
+        let mut mock = pa_spl.release();
         mock.done();
     }
 
@@ -534,26 +1217,173 @@ mod tests {
         let control_register_default_bits = ControlRegister::from_bits(REG_CONTROL_DEFAULT);
         assert_eq!(control_register_default_bits, reg_control);
 
-        let mut mock = pa_spl.destroy();
+        let mut mock = pa_spl.release();
         mock.done();
     }
 
-    #[cfg(feature = "external_mic")]
     #[test]
     fn confirm_get_gain() {
+        let expectations = vec![
+            I2cTransaction::write_read(
+                DEVICE_ADDR_DEFAULT,
+                vec![REG_VERSION],
+                vec![DEVICE_VER_EXT_MIC],
+            ),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_GAIN], vec![18]),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let expected_gain = Gain::from_db(9.0).unwrap();
+        let gain_val = pa_spl.get_gain().unwrap();
+        assert_eq!(expected_gain, gain_val);
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_get_gain_out_of_range() {
+        let expectations = vec![
+            I2cTransaction::write_read(
+                DEVICE_ADDR_DEFAULT,
+                vec![REG_VERSION],
+                vec![DEVICE_VER_EXT_MIC],
+            ),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_GAIN], vec![96]),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let expected = Error::InvalidRegisterValue {
+            register: REG_GAIN,
+            value: 96,
+        };
+        assert_eq!(Err(expected), pa_spl.get_gain());
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_gain_unsupported() {
         let expectations = vec![I2cTransaction::write_read(
             DEVICE_ADDR_DEFAULT,
-            vec![REG_GAIN],
-            vec![18],
+            vec![REG_VERSION],
+            vec![DEVICE_VER_MEMS_LTS_ASA],
         )];
         let i2c_mock = I2cMock::new(&expectations);
         let mut pa_spl = PaSpl::new(i2c_mock);
 
-        let expected_gain: u8 = 18;
-        let gain_val = pa_spl.get_gain().unwrap();
-        assert_eq!(expected_gain, gain_val);
+        let expected = Error::Unsupported(Variant::MemsSpectrumAnalyzer);
+        assert_eq!(Err(expected), pa_spl.get_gain());
+        let expected = Error::Unsupported(Variant::MemsSpectrumAnalyzer);
+        assert_eq!(Err(expected), pa_spl.set_gain(Gain::DEFAULT));
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_set_line_out() {
+        let expectations = vec![
+            I2cTransaction::write_read(
+                DEVICE_ADDR_DEFAULT,
+                vec![REG_VERSION],
+                vec![DEVICE_VER_EXT_MIC],
+            ),
+            I2cTransaction::write_read(
+                DEVICE_ADDR_DEFAULT,
+                vec![REG_CONTROL],
+                vec![REG_CONTROL_DEFAULT],
+            ),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0b0010_0010]),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL], vec![0b0010_0010]),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        pa_spl.set_line_out(true).unwrap();
+        assert!(pa_spl.get_line_out().unwrap());
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_reserved_encodings_decode() {
+        let reg_control = ControlRegister::from_bits(0b0000_0110);
+        assert_eq!(FilterSetting::Reserved, reg_control.filter());
+        assert!(reg_control.has_reserved_bits());
+
+        let reg_control = ControlRegister::from_bits(0b1000_0010);
+        assert_eq!(FilterSetting::AWeighting, reg_control.filter());
+        assert!(reg_control.has_reserved_bits());
+
+        assert!(!ControlRegister::from_bits(REG_CONTROL_DEFAULT).has_reserved_bits());
+    }
 
-        let mut mock = pa_spl.destroy();
+    #[test]
+    fn confirm_reserved_bits_not_written() {
+        let expectations = vec![
+            I2cTransaction::write_read(
+                DEVICE_ADDR_DEFAULT,
+                vec![REG_VERSION],
+                vec![DEVICE_VER_EXT_MIC],
+            ),
+            // A corrupted read of CONTROL is not written back.
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL], vec![0b1000_0010]),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let mut reg_control = ControlRegister::new();
+        reg_control.set_filter(FilterSetting::Reserved);
+        assert_eq!(
+            Err(Error::ReservedBits {
+                register: REG_CONTROL,
+                value: 0b0000_0110,
+            }),
+            pa_spl.set_control_register(reg_control)
+        );
+        assert_eq!(
+            Err(Error::ReservedBits {
+                register: REG_RESET,
+                value: 0b0001_0000,
+            }),
+            pa_spl.set_reset_register(ResetRegister::from_bits(0b0001_0000))
+        );
+        assert_eq!(
+            Err(Error::ReservedBits {
+                register: REG_CONTROL,
+                value: 0b1010_0010,
+            }),
+            pa_spl.set_line_out(true)
+        );
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_line_out_unsupported() {
+        let expectations = vec![I2cTransaction::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_VERSION],
+            vec![DEVICE_VER_MEMS_LTS],
+        )];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let expected = Err(Error::Unsupported(Variant::MemsLts));
+        assert_eq!(expected, pa_spl.set_line_out(true));
+
+        // Writing the Enable Line Output bit directly is rejected too.
+        let mut reg_control = ControlRegister::from_bits(REG_CONTROL_DEFAULT);
+        reg_control.set_line_out(true);
+        assert_eq!(expected, pa_spl.set_control_register(reg_control));
+
+        let mut mock = pa_spl.release();
         mock.done();
     }
 
@@ -570,7 +1400,62 @@ mod tests {
         let latest_decibel_val = pa_spl.get_latest_decibel().unwrap();
         assert_eq!(0x12, latest_decibel_val);
 
-        let mut mock = pa_spl.destroy();
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_read_history() {
+        let history: Vec<u8> = (0..HISTORY_LEN as u8).map(|i| 40 + i).collect();
+        let expectations = read_registers(DEVICE_ADDR_DEFAULT, REG_DBHISTORY_0, history.clone());
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let result = pa_spl.read_history().unwrap();
+        assert_eq!(HISTORY_LEN, result.len());
+        assert_eq!(history.as_slice(), result.as_slice());
+        assert_eq!(Some(40), result.newest());
+        assert_eq!(Some(139), result.oldest());
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_read_history_range() {
+        let expectations =
+            read_registers(DEVICE_ADDR_DEFAULT, REG_DBHISTORY_0 + 10, vec![60, 61, 62]);
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let result = pa_spl.read_history_range(10, 3).unwrap();
+        assert_eq!(10, result.start());
+        assert_eq!(&[60, 61, 62], result.as_slice());
+        assert_eq!(Some(61), result.get(11));
+        assert_eq!(None, result.get(9));
+        assert_eq!(None, result.get(13));
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_read_history_range_rejects_out_of_bounds() {
+        let expectations = vec![];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        assert_eq!(
+            Err(Error::InvalidArgument),
+            pa_spl.read_history_range(99, 2)
+        );
+        assert_eq!(Err(Error::InvalidArgument), pa_spl.read_history_range(0, 0));
+        assert_eq!(
+            Err(Error::InvalidArgument),
+            pa_spl.read_history_range(usize::MAX, 1)
+        );
+
+        let mut mock = pa_spl.release();
         mock.done();
     }
 
@@ -587,7 +1472,7 @@ mod tests {
         let result = pa_spl.get_max_decibel();
         assert!(result.is_ok());
 
-        let mut mock = pa_spl.destroy();
+        let mut mock = pa_spl.release();
         mock.done();
     }
 
@@ -604,10 +1489,79 @@ mod tests {
         let result = pa_spl.get_min_decibel();
         assert!(result.is_ok());
 
-        let mut mock = pa_spl.destroy();
+        let mut mock = pa_spl.release();
         mock.done();
     }
 
+    #[test]
+    fn confirm_read_spectrum() {
+        let bins: Vec<u8> = (0..SPECTRUM_BINS as u8).map(|i| 30 + i).collect();
+        let mut expectations = vec![I2cTransaction::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_VERSION],
+            vec![DEVICE_VER_MEMS_LTS_ASA],
+        )];
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_FREQ_64BINS_0,
+            bins.clone(),
+        ));
+        // The version is cached, so the second read only reads the bins.
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_FREQ_64BINS_0,
+            bins.clone(),
+        ));
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let spectrum = pa_spl.read_spectrum().unwrap();
+        assert_eq!(bins.as_slice(), spectrum.bins());
+        let spectrum = pa_spl.read_spectrum().unwrap();
+        assert_eq!((7937.5, 93), spectrum.peak());
+
+        let (first_hz, first_db) = spectrum.iter().next().unwrap();
+        assert_eq!(62.5, first_hz);
+        assert_eq!(30, first_db);
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_read_spectrum_unsupported() {
+        let expectations = vec![I2cTransaction::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_VERSION],
+            vec![0x31],
+        )];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        assert_eq!(
+            Err(Error::Unsupported(Variant::MemsLts)),
+            pa_spl.read_spectrum()
+        );
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_spectrum_bin_frequencies() {
+        assert_eq!(125.0, SPECTRUM_BIN_WIDTH_HZ);
+        assert_eq!(Some(62.5), Spectrum::center_frequency_hz(0));
+        assert_eq!(
+            Some(7937.5),
+            Spectrum::center_frequency_hz(SPECTRUM_BINS - 1)
+        );
+        assert_eq!(None, Spectrum::center_frequency_hz(SPECTRUM_BINS));
+        assert_eq!(Some(8), Spectrum::bin_index(1000.0));
+        assert_eq!(Some(SPECTRUM_BINS - 1), Spectrum::bin_index(7999.0));
+        assert_eq!(None, Spectrum::bin_index(8000.0));
+        assert_eq!(None, Spectrum::bin_index(-1.0));
+    }
+
     #[test]
     fn confirm_get_scratch() {
         let expectations = vec![I2cTransaction::write_read(
@@ -622,7 +1576,7 @@ mod tests {
         let scratch_read_val = pa_spl.get_scratch().unwrap();
         assert_eq!(scratch_write_val, scratch_read_val);
 
-        let mut mock = pa_spl.destroy();
+        let mut mock = pa_spl.release();
         mock.done();
     }
 
@@ -638,13 +1592,50 @@ mod tests {
         let result = pa_spl.reset();
         assert!(result.is_ok());
 
-        let mut mock = pa_spl.destroy();
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_clear_operations() {
+        let expectations = vec![
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_RESET, 0b0000_0001]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_RESET, 0b0000_0010]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_RESET, 0b0000_0100]),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        assert!(pa_spl.clear_interrupt().is_ok());
+        assert!(pa_spl.clear_min_max().is_ok());
+        assert!(pa_spl.clear_history().is_ok());
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_set_reset_register() {
+        let expectations = vec![I2cTransaction::write(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_RESET, 0b0000_0110],
+        )];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let reg_reset = ResetRegister::new()
+            .with_clear_min_max(true)
+            .with_clear_history(true);
+        let result = pa_spl.set_reset_register(reg_reset);
+        assert!(result.is_ok());
+
+        let mut mock = pa_spl.release();
         mock.done();
     }
 
     #[test]
     fn confirm_set_avg_time() {
-        let new_avg_time_ms: u16 = 125;
+        let new_avg_time = AveragingTime::FAST;
         let tavg_high_expected_byte: u8 = 0x00;
         let tavg_low_expected_byte: u8 = 0x7D;
         let expectations = vec![I2cTransaction::write(
@@ -658,10 +1649,10 @@ mod tests {
         let i2c_mock = I2cMock::new(&expectations);
         let mut pa_spl = PaSpl::new(i2c_mock);
 
-        let result = pa_spl.set_avg_time(new_avg_time_ms);
+        let result = pa_spl.set_avg_time(new_avg_time);
         assert!(result.is_ok());
 
-        let mut mock = pa_spl.destroy();
+        let mut mock = pa_spl.release();
         mock.done();
     }
 
@@ -687,25 +1678,191 @@ mod tests {
         let result = pa_spl.set_control_register(reg_control);
         assert!(result.is_ok());
 
-        let mut mock = pa_spl.destroy();
+        let mut mock = pa_spl.release();
         mock.done();
     }
 
-    #[cfg(feature = "external_mic")]
     #[test]
     fn confirm_set_gain() {
         let new_gain_val: u8 = 43;
+        let expectations = vec![
+            I2cTransaction::write_read(
+                DEVICE_ADDR_DEFAULT,
+                vec![REG_VERSION],
+                vec![DEVICE_VER_EXT_MIC],
+            ),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_GAIN, new_gain_val]),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let result = pa_spl.set_gain(Gain::from_db(21.5).unwrap());
+        assert!(result.is_ok());
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_get_threshold_window() {
+        let expectations = read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_THR_MIN,
+            vec![REG_THR_MIN_DEFAULT, REG_THR_MAX_DEFAULT],
+        );
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let window = pa_spl.get_threshold_window().unwrap();
+        assert_eq!((REG_THR_MIN_DEFAULT, REG_THR_MAX_DEFAULT), window);
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_set_threshold_min_max() {
+        let expectations = vec![
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_THR_MIN, 50]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_THR_MAX, 90]),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_THR_MIN], vec![50]),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_THR_MAX], vec![90]),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        pa_spl.set_threshold_min(50).unwrap();
+        pa_spl.set_threshold_max(90).unwrap();
+        assert_eq!(50, pa_spl.get_threshold_min().unwrap());
+        assert_eq!(90, pa_spl.get_threshold_max().unwrap());
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_set_threshold_window() {
         let expectations = vec![I2cTransaction::write(
             DEVICE_ADDR_DEFAULT,
-            vec![REG_GAIN, new_gain_val],
+            vec![REG_THR_MIN, 40, 95],
         )];
         let i2c_mock = I2cMock::new(&expectations);
         let mut pa_spl = PaSpl::new(i2c_mock);
 
-        let result = pa_spl.set_gain(new_gain_val);
+        let result = pa_spl.set_threshold_window(40, 95);
         assert!(result.is_ok());
 
-        let mut mock = pa_spl.destroy();
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_set_threshold_window_rejects_inverted_window() {
+        let expectations = vec![];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let result = pa_spl.set_threshold_window(95, 40);
+        assert_eq!(Err(Error::InvalidArgument), result);
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_verify_writes() {
+        let mut expectations = vec![I2cTransaction::write(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_THR_MIN, 40, 95],
+        )];
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_THR_MIN,
+            vec![40, 95],
+        ));
+        expectations.extend([
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0b0000_0100]),
+            // The reserved bits of CONTROL are not compared.
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL], vec![0b1100_0100]),
+            // SCRATCH is not a configuration register, so it is not verified.
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_SCRATCH, 0x55]),
+        ]);
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+        pa_spl.set_verify_writes(true);
+        assert!(pa_spl.verify_writes());
+
+        let mut reg_control = ControlRegister::new();
+        reg_control.set_filter(FilterSetting::CWeighting);
+        assert_eq!(Ok(()), pa_spl.set_threshold_window(40, 95));
+        assert_eq!(Ok(()), pa_spl.set_control_register(reg_control));
+        assert_eq!(Ok(()), pa_spl.set_scratch(0x55));
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_verify_writes_mismatch() {
+        let mut expectations = vec![I2cTransaction::write(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_TAVG_HIGH, 0x00, 0x7D],
+        )];
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_TAVG_HIGH,
+            vec![0x00, 0xE8],
+        ));
+        expectations.extend([
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0b0000_1000]),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL], vec![0b0000_0010]),
+        ]);
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+        pa_spl.set_verify_writes(true);
+
+        assert_eq!(
+            Err(Error::VerifyFailed {
+                register: REG_TAVG_LOW,
+                written: 0x7D,
+                read: 0xE8,
+            }),
+            pa_spl.set_avg_time(AveragingTime::FAST)
+        );
+        let mut reg_control = ControlRegister::new();
+        reg_control.set_interrupt_mode(InterruptMode::History);
+        assert_eq!(
+            Err(Error::VerifyFailed {
+                register: REG_CONTROL,
+                written: 0b0000_1000,
+                read: 0b0000_0010,
+            }),
+            pa_spl.set_control_register(reg_control)
+        );
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_enable_threshold_interrupt() {
+        let expectations = vec![
+            I2cTransaction::write_read(
+                DEVICE_ADDR_DEFAULT,
+                vec![REG_CONTROL],
+                vec![REG_CONTROL_DEFAULT], // 0b0000_0010
+            ),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0b0001_1010]),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL], vec![0b0001_1010]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, REG_CONTROL_DEFAULT]),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        pa_spl.enable_threshold_interrupt().unwrap();
+        pa_spl.disable_threshold_interrupt().unwrap();
+
+        let mut mock = pa_spl.release();
         mock.done();
     }
 
@@ -722,7 +1879,34 @@ mod tests {
         let result = pa_spl.set_scratch(scratch_write_val);
         assert!(result.is_ok());
 
-        let mut mock = pa_spl.destroy();
+        let mut mock = pa_spl.release();
         mock.done();
     }
+
+    #[test]
+    fn confirm_verify_scratch_read_back_error_after_restore() {
+        let expectations = vec![
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_SCRATCH], vec![0xaa]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_SCRATCH, 0x55]),
+            with_bus_error(I2cTransaction::write_read(
+                DEVICE_ADDR_DEFAULT,
+                vec![REG_SCRATCH],
+                vec![0x55],
+            )),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_SCRATCH, 0xaa]),
+        ];
+        let mut i2c_mock = I2cMock::new(&expectations);
+
+        let error = verify_scratch(&mut i2c_mock, DEVICE_ADDR_DEFAULT).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::I2c {
+                register: REG_SCRATCH,
+                operation: Operation::Read,
+                ..
+            }
+        ));
+
+        i2c_mock.done();
+    }
 }
@@ -0,0 +1,89 @@
+//! Mocks of the embedded-hal generation under test, so the tests run against
+//! either. With both features enabled the embedded-hal 1.0 mocks are used.
+
+#[cfg(all(feature = "eh0", not(feature = "eh1")))]
+pub use embedded_hal_mock::eh0::{
+    digital::{State as PinState, Transaction as PinTransaction},
+    i2c::Transaction as I2cTransaction,
+};
+#[cfg(feature = "eh1")]
+pub use embedded_hal_mock::eh1::{
+    delay::NoopDelay,
+    digital::{Mock as PinMock, State as PinState, Transaction as PinTransaction},
+    i2c::{Mock as I2cMock, Transaction as I2cTransaction},
+};
+
+#[cfg(all(feature = "eh0", not(feature = "eh1")))]
+pub use eh0::{I2cMock, NoopDelay, PinMock};
+
+/// The embedded-hal 0.2 mocks wrapped in `Eh0`, with the constructors and
+/// `done` of the mocks they wrap.
+#[cfg(all(feature = "eh0", not(feature = "eh1")))]
+mod eh0 {
+    use super::{I2cTransaction, PinTransaction};
+    use crate::hal::Eh0;
+    use embedded_hal_mock::eh0::{delay, digital, i2c};
+
+    pub type I2cMock = Eh0<i2c::Mock>;
+    pub type PinMock = Eh0<digital::Mock>;
+    pub type NoopDelay = Eh0<delay::NoopDelay>;
+
+    impl I2cMock {
+        pub fn new(expectations: &[I2cTransaction]) -> Self {
+            Eh0(i2c::Mock::new(expectations))
+        }
+
+        pub fn done(&mut self) {
+            self.0.done();
+        }
+    }
+
+    impl PinMock {
+        pub fn new(expectations: &[PinTransaction]) -> Self {
+            Eh0(digital::Mock::new(expectations))
+        }
+
+        pub fn done(&mut self) {
+            self.0.done();
+        }
+    }
+
+    impl NoopDelay {
+        pub fn new() -> Self {
+            Eh0(delay::NoopDelay::new())
+        }
+    }
+
+    impl Default for NoopDelay {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+}
+
+/// Expectations for reading consecutive registers starting at `start_reg`.
+///
+/// With embedded-hal 0.2 this is a write-read; with embedded-hal 1.0 it is a
+/// transaction of a write followed by a read.
+pub fn read_registers(addr: u8, start_reg: u8, data: Vec<u8>) -> Vec<I2cTransaction> {
+    #[cfg(not(feature = "eh1"))]
+    let expectations = vec![I2cTransaction::write_read(addr, vec![start_reg], data)];
+    #[cfg(feature = "eh1")]
+    let expectations = vec![
+        I2cTransaction::transaction_start(addr),
+        I2cTransaction::write(addr, vec![start_reg]),
+        I2cTransaction::read(addr, data),
+        I2cTransaction::transaction_end(addr),
+    ];
+    expectations
+}
+
+/// Makes an expected transaction fail with a bus error.
+///
+pub fn with_bus_error(transaction: I2cTransaction) -> I2cTransaction {
+    #[cfg(not(feature = "eh1"))]
+    let error = embedded_hal_mock::eh0::MockError::Io(std::io::ErrorKind::Other);
+    #[cfg(feature = "eh1")]
+    let error = embedded_hal::i2c::ErrorKind::Other;
+    transaction.with_error(error)
+}
@@ -0,0 +1,296 @@
+//! Power-down and wake-up of the module.
+
+use crate::hal::{Delay, I2cBus};
+use crate::{Config, ConfigDiff, Error, PaSpl, ResetRegister, REG_CONTROL, REG_RESET};
+
+/// Time in ms allowed for the module to come out of a system reset before it
+/// is reconfigured.
+///
+/// The programming manual states the module is ready for communication over
+/// I2C within 5 ms of power-up.
+pub const RESET_DELAY_MS: u32 = 5;
+
+/// A PA SPL Module that has been powered down.
+///
+/// A powered down module does not take readings, so a sleeping driver only
+/// offers [`SleepingPaSpl::wake`] and releasing the I2C bus.
+pub struct SleepingPaSpl<I2C>
+where
+    I2C: I2cBus,
+{
+    driver: PaSpl<I2C>,
+    /// Configuration saved when the module was powered down and re-applied
+    /// when it is woken up, since waking the module restores its power-up
+    /// defaults.
+    saved: Config,
+}
+
+impl<E, I2C> PaSpl<I2C>
+where
+    I2C: I2cBus<Error = E>,
+{
+    /// Powers down the sensor.
+    ///
+    /// Saves the current configuration (CONTROL, TAVG, THR_MIN/THR_MAX, and
+    /// GAIN where supported), then sets the Power Down bit in the CONTROL
+    /// register. The returned driver restores the configuration when it is
+    /// woken up with [`SleepingPaSpl::wake`]. A TAVG register holding an
+    /// invalid value is restored as the default averaging time, and a GAIN
+    /// register holding an invalid value is left at its power-up default.
+    ///
+    /// # Errors
+    ///
+    /// Returns the driver along with [`Error::I2c`] if I2C returns an error.
+    /// The sensor is still powered up unless the final write to CONTROL failed
+    /// after reaching the device.
+    ///
+    pub fn power_down(mut self) -> Result<SleepingPaSpl<I2C>, (Self, Error<E>)> {
+        match self.save_and_power_down() {
+            Ok(saved) => Ok(SleepingPaSpl {
+                driver: self,
+                saved,
+            }),
+            Err(e) => Err((self, e)),
+        }
+    }
+
+    fn save_and_power_down(&mut self) -> Result<Config, Error<E>> {
+        let (config, _) = self.read_live_config()?;
+
+        let mut reg_control = config.control_register();
+        reg_control.set_power_down(true);
+        self.write_byte(REG_CONTROL, reg_control.into_bits())?;
+
+        Ok(config)
+    }
+}
+
+impl<E, I2C> SleepingPaSpl<I2C>
+where
+    I2C: I2cBus<Error = E>,
+{
+    /// Wakes up the sensor.
+    ///
+    /// Sets the System Reset bit in the RESET register, as the manual requires
+    /// to wake the module, waits [`RESET_DELAY_MS`] for the reset, then
+    /// re-applies the configuration saved by [`PaSpl::power_down`].
+    ///
+    /// The decibel reading is only valid about 1 second after wake-up, which
+    /// [`ClockedPaSpl`](crate::ClockedPaSpl) tracks after a call to
+    /// [`ClockedPaSpl::mark_power_up`](crate::ClockedPaSpl::mark_power_up).
+    ///
+    /// # Errors
+    ///
+    /// Returns the sleeping driver along with [`Error::I2c`] if I2C returns an
+    /// error. Waking up again performs a fresh reset, so it is safe to retry.
+    ///
+    pub fn wake<D>(mut self, delay: &mut D) -> Result<PaSpl<I2C>, (Self, Error<E>)>
+    where
+        D: Delay,
+    {
+        match self.reset_and_restore(delay) {
+            Ok(()) => Ok(self.driver),
+            Err(e) => Err((self, e)),
+        }
+    }
+
+    /// Destroys this driver and releases the I2C bus.
+    ///
+    /// The sensor is left powered down.
+    ///
+    pub fn release(self) -> I2C {
+        self.driver.release()
+    }
+
+    fn reset_and_restore<D>(&mut self, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: Delay,
+    {
+        let reg_reset = ResetRegister::new().with_system_reset(true);
+        self.driver.write_byte(REG_RESET, reg_reset.into_bits())?;
+        delay.delay_ms(RESET_DELAY_MS);
+
+        self.driver.write_config(&self.saved, &ConfigDiff::ALL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::{read_registers, with_bus_error, I2cMock, I2cTransaction, NoopDelay};
+    use crate::{
+        PaSpl, DEVICE_ADDR_DEFAULT, REG_CONTROL, REG_GAIN, REG_RESET, REG_TAVG_HIGH, REG_THR_MIN,
+        REG_VERSION,
+    };
+
+    /// Expectations for saving a non-default configuration of a module
+    /// without adjustable gain.
+    fn save_expectations() -> Vec<I2cTransaction> {
+        let mut expectations = vec![I2cTransaction::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_CONTROL],
+            vec![0b0001_1100],
+        )];
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_TAVG_HIGH,
+            vec![0x00, 0x7D],
+        ));
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_THR_MIN,
+            vec![40, 95],
+        ));
+        expectations.push(I2cTransaction::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_VERSION],
+            vec![0x32],
+        ));
+        expectations
+    }
+
+    #[test]
+    fn confirm_power_down_and_wake() {
+        let mut expectations = save_expectations();
+        expectations.extend([
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0b0001_1101]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_RESET, 0b0000_1000]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_TAVG_HIGH, 0x00, 0x7D]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_THR_MIN, 40, 95]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0b0001_1100]),
+        ]);
+        let i2c_mock = I2cMock::new(&expectations);
+        let pa_spl = PaSpl::new(i2c_mock);
+
+        let sleeping = pa_spl.power_down().map_err(|(_, e)| e).unwrap();
+        let pa_spl = sleeping
+            .wake(&mut NoopDelay::new())
+            .map_err(|(_, e)| e)
+            .unwrap();
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_wake_restores_gain() {
+        let mut expectations = vec![I2cTransaction::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_CONTROL],
+            vec![0b0010_0010],
+        )];
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_TAVG_HIGH,
+            vec![0x03, 0xE8],
+        ));
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_THR_MIN,
+            vec![45, 85],
+        ));
+        expectations.extend([
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_VERSION], vec![0x81]),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_GAIN], vec![30]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0b0010_0011]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_RESET, 0b0000_1000]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_TAVG_HIGH, 0x03, 0xE8]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_THR_MIN, 45, 85]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_GAIN, 30]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0b0010_0010]),
+        ]);
+        let i2c_mock = I2cMock::new(&expectations);
+        let pa_spl = PaSpl::new(i2c_mock);
+
+        let sleeping = pa_spl.power_down().map_err(|(_, e)| e).unwrap();
+        let pa_spl = sleeping
+            .wake(&mut NoopDelay::new())
+            .map_err(|(_, e)| e)
+            .unwrap();
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_wake_can_be_retried() {
+        let mut expectations = save_expectations();
+        expectations.extend([
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0b0001_1101]),
+            with_bus_error(I2cTransaction::write(
+                DEVICE_ADDR_DEFAULT,
+                vec![REG_RESET, 0b0000_1000],
+            )),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_RESET, 0b0000_1000]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_TAVG_HIGH, 0x00, 0x7D]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_THR_MIN, 40, 95]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0b0001_1100]),
+        ]);
+        let i2c_mock = I2cMock::new(&expectations);
+        let pa_spl = PaSpl::new(i2c_mock);
+
+        let sleeping = pa_spl.power_down().map_err(|(_, e)| e).unwrap();
+        let (sleeping, _) = sleeping.wake(&mut NoopDelay::new()).err().unwrap();
+        let pa_spl = sleeping
+            .wake(&mut NoopDelay::new())
+            .map_err(|(_, e)| e)
+            .unwrap();
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_power_down_over_invalid_avg_time() {
+        let mut expectations = vec![I2cTransaction::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_CONTROL],
+            vec![0x02],
+        )];
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_TAVG_HIGH,
+            vec![0x00, 0x00],
+        ));
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_THR_MIN,
+            vec![45, 85],
+        ));
+        expectations.extend([
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_VERSION], vec![0x32]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0x03]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_RESET, 0b0000_1000]),
+            // The invalid TAVG is restored as the default averaging time.
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_TAVG_HIGH, 0x03, 0xE8]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_THR_MIN, 45, 85]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0x02]),
+        ]);
+        let i2c_mock = I2cMock::new(&expectations);
+        let pa_spl = PaSpl::new(i2c_mock);
+
+        let sleeping = pa_spl.power_down().map_err(|(_, e)| e).unwrap();
+        let pa_spl = sleeping
+            .wake(&mut NoopDelay::new())
+            .map_err(|(_, e)| e)
+            .unwrap();
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_power_down_failure_returns_driver() {
+        let expectations = vec![with_bus_error(I2cTransaction::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_CONTROL],
+            vec![0x02],
+        ))];
+        let i2c_mock = I2cMock::new(&expectations);
+        let pa_spl = PaSpl::new(i2c_mock);
+
+        let (pa_spl, _) = pa_spl.power_down().err().unwrap();
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+}
@@ -0,0 +1,293 @@
+//! Retry of failed I2C accesses.
+
+use defmt::Format;
+
+use crate::hal::{BusError, Delay, ErrorKind, I2cBus};
+use crate::PaSpl;
+
+/// When and how often a failed I2C access is retried by [`Retry`].
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts of an access, including the first one. At
+    /// least one attempt is always made.
+    pub max_attempts: u8,
+    /// Delay in ms between two attempts.
+    pub delay_ms: u32,
+    /// Returns true if an error of the given kind is worth retrying.
+    pub retryable: fn(ErrorKind) -> bool,
+}
+
+impl RetryPolicy {
+    /// Returns true for the error kinds of transient bus faults: a missing
+    /// acknowledge, lost arbitration, a bus error, and other errors.
+    ///
+    /// Other errors are included because with the `eh0` feature every error
+    /// is [`ErrorKind::Other`].
+    ///
+    pub fn is_transient(kind: ErrorKind) -> bool {
+        matches!(
+            kind,
+            ErrorKind::NoAcknowledge(_)
+                | ErrorKind::ArbitrationLoss
+                | ErrorKind::Bus
+                | ErrorKind::Other
+        )
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts 1 ms apart, retrying transient bus faults.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            delay_ms: 1,
+            retryable: Self::is_transient,
+        }
+    }
+}
+
+/// Counters of the accesses retried by [`Retry`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Format)]
+pub struct RetryStats {
+    /// Number of attempts repeated after a retryable error.
+    pub retries: u32,
+    /// Number of accesses that failed for good, because the error was not
+    /// retryable or every attempt failed.
+    pub failures: u32,
+}
+
+/// The I2C bus `I2C` with failed accesses retried according to a
+/// [`RetryPolicy`], waiting between attempts with the delay provider `D`.
+///
+/// Use it as the bus of a [`PaSpl`] driver, e.g. with
+/// [`PaSpl::with_retry`], to ride out occasional faults on long cables.
+pub struct Retry<I2C, D> {
+    i2c: I2C,
+    delay: D,
+    policy: RetryPolicy,
+    stats: RetryStats,
+}
+
+impl<I2C, D> Retry<I2C, D>
+where
+    I2C: I2cBus,
+    I2C::Error: BusError,
+    D: Delay,
+{
+    /// Wraps the I2C bus `i2c`, retrying with `policy` and waiting with
+    /// `delay`.
+    ///
+    pub fn new(i2c: I2C, delay: D, policy: RetryPolicy) -> Self {
+        Self {
+            i2c,
+            delay,
+            policy,
+            stats: RetryStats::default(),
+        }
+    }
+
+    /// Gets the retry policy.
+    ///
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Gets the counters of retried and failed accesses.
+    ///
+    pub fn stats(&self) -> RetryStats {
+        self.stats
+    }
+
+    /// Resets the counters of retried and failed accesses to zero.
+    ///
+    pub fn reset_stats(&mut self) {
+        self.stats = RetryStats::default();
+    }
+
+    /// Destroys this wrapper and releases the I2C bus and the delay provider.
+    ///
+    pub fn release(self) -> (I2C, D) {
+        (self.i2c, self.delay)
+    }
+
+    /// Runs the access `f` on the bus until it succeeds, fails with an error
+    /// that is not retryable, or runs out of attempts.
+    ///
+    fn retry<T, F>(&mut self, mut f: F) -> Result<T, I2C::Error>
+    where
+        F: FnMut(&mut I2C) -> Result<T, I2C::Error>,
+    {
+        let mut attempt = 1;
+        loop {
+            match f(&mut self.i2c) {
+                Ok(value) => return Ok(value),
+                Err(error)
+                    if attempt < self.policy.max_attempts
+                        && (self.policy.retryable)(error.kind()) =>
+                {
+                    self.stats.retries = self.stats.retries.saturating_add(1);
+                    attempt += 1;
+                    self.delay.delay_ms(self.policy.delay_ms);
+                }
+                Err(error) => {
+                    self.stats.failures = self.stats.failures.saturating_add(1);
+                    return Err(error);
+                }
+            }
+        }
+    }
+}
+
+impl<I2C, D> I2cBus for Retry<I2C, D>
+where
+    I2C: I2cBus,
+    I2C::Error: BusError,
+    D: Delay,
+{
+    type Error = I2C::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.retry(|i2c| i2c.write(address, bytes))
+    }
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.retry(|i2c| i2c.write_read(address, bytes, buffer))
+    }
+
+    fn read_registers(
+        &mut self,
+        address: u8,
+        start_reg: u8,
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.retry(|i2c| i2c.read_registers(address, start_reg, buffer))
+    }
+}
+
+impl<I2C, D> PaSpl<Retry<I2C, D>>
+where
+    I2C: I2cBus,
+    I2C::Error: BusError,
+    D: Delay,
+{
+    /// Initializes the driver on the I2C bus `i2c`, retrying failed accesses
+    /// with `policy` and waiting between attempts with `delay`.
+    ///
+    pub fn with_retry(i2c: I2C, delay: D, policy: RetryPolicy) -> Self {
+        PaSpl::new(Retry::new(i2c, delay, policy))
+    }
+
+    /// Gets the counters of retried and failed accesses.
+    ///
+    pub fn retry_stats(&self) -> RetryStats {
+        self.i2c.stats()
+    }
+
+    /// Resets the counters of retried and failed accesses to zero.
+    ///
+    pub fn reset_retry_stats(&mut self) {
+        self.i2c.reset_stats();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RetryPolicy, RetryStats};
+    use crate::mock::{with_bus_error, I2cMock, I2cTransaction, NoopDelay};
+    use crate::{Error, PaSpl, DEVICE_ADDR_DEFAULT, REG_DECIBEL, REG_SCRATCH};
+
+    /// Expectation for reading DECIBEL, failing with a bus error if `fail`.
+    fn read_decibel(fail: bool) -> I2cTransaction {
+        let transaction =
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_DECIBEL], vec![62]);
+        if fail {
+            with_bus_error(transaction)
+        } else {
+            transaction
+        }
+    }
+
+    #[test]
+    fn confirm_retry_until_success() {
+        let expectations = vec![read_decibel(true), read_decibel(true), read_decibel(false)];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::with_retry(i2c_mock, NoopDelay::new(), RetryPolicy::default());
+
+        assert_eq!(Ok(62), pa_spl.get_latest_decibel());
+        assert_eq!(
+            RetryStats {
+                retries: 2,
+                failures: 0,
+            },
+            pa_spl.retry_stats()
+        );
+
+        let (mut mock, _) = pa_spl.release().release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_retry_gives_up() {
+        let expectations = vec![
+            read_decibel(true),
+            read_decibel(true),
+            read_decibel(true),
+            read_decibel(false),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::with_retry(i2c_mock, NoopDelay::new(), RetryPolicy::default());
+
+        assert!(matches!(
+            pa_spl.get_latest_decibel(),
+            Err(Error::I2c {
+                register: REG_DECIBEL,
+                ..
+            })
+        ));
+        assert_eq!(Ok(62), pa_spl.get_latest_decibel());
+        assert_eq!(
+            RetryStats {
+                retries: 2,
+                failures: 1,
+            },
+            pa_spl.retry_stats()
+        );
+
+        pa_spl.reset_retry_stats();
+        assert_eq!(RetryStats::default(), pa_spl.retry_stats());
+
+        let (mut mock, _) = pa_spl.release().release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_no_retry_of_unretryable_kind() {
+        let expectations = vec![with_bus_error(I2cTransaction::write(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_SCRATCH, 0x55],
+        ))];
+        let i2c_mock = I2cMock::new(&expectations);
+        let policy = RetryPolicy {
+            retryable: |_| false,
+            ..RetryPolicy::default()
+        };
+        let mut pa_spl = PaSpl::with_retry(i2c_mock, NoopDelay::new(), policy);
+
+        assert!(pa_spl.set_scratch(0x55).is_err());
+        assert_eq!(
+            RetryStats {
+                retries: 0,
+                failures: 1,
+            },
+            pa_spl.retry_stats()
+        );
+
+        let (mut mock, _) = pa_spl.release().release();
+        mock.done();
+    }
+}
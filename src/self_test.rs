@@ -0,0 +1,218 @@
+//! Communication self-test through the SCRATCH register.
+
+use defmt::Format;
+
+use crate::hal::I2cBus;
+use crate::{Error, PaSpl, Variant, CONTROL_RESERVED_MASK, REG_CONTROL, REG_SCRATCH};
+
+/// Alternating bit patterns written to SCRATCH by [`PaSpl::self_test`].
+pub(crate) const ALTERNATING_PATTERNS: [u8; 2] = [0x55, 0xaa];
+
+/// Outcome of each check of [`PaSpl::self_test`].
+///
+/// A field is true if the check passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Format)]
+pub struct SelfTestReport {
+    /// Every walking-ones pattern (0x01, 0x02, ..., 0x80) written to SCRATCH
+    /// was read back unchanged.
+    pub walking_ones: bool,
+    /// Every walking-zeros pattern (0xFE, 0xFD, ..., 0x7F) written to SCRATCH
+    /// was read back unchanged.
+    pub walking_zeros: bool,
+    /// The alternating patterns 0x55 and 0xAA written to SCRATCH were read
+    /// back unchanged.
+    pub alternating: bool,
+    /// SCRATCH holds its original value again after the test.
+    pub scratch_restored: bool,
+    /// VERSION identifies a known variant of the module.
+    pub version: bool,
+    /// The reserved bits of CONTROL are clear.
+    pub control_reserved_bits: bool,
+}
+
+impl SelfTestReport {
+    /// Returns true if every check passed.
+    ///
+    pub const fn passed(&self) -> bool {
+        self.walking_ones
+            && self.walking_zeros
+            && self.alternating
+            && self.scratch_restored
+            && self.version
+            && self.control_reserved_bits
+    }
+}
+
+impl<E, I2C> PaSpl<I2C>
+where
+    I2C: I2cBus<Error = E>,
+{
+    /// Runs a self-test of the I2C communication with the module.
+    ///
+    /// Writes walking-ones, walking-zeros, and alternating bit patterns to the
+    /// SCRATCH register and reads each one back, as the manual recommends for
+    /// verifying I2C, then restores the original SCRATCH value. Also checks
+    /// that VERSION identifies a known variant and that the reserved bits of
+    /// CONTROL are clear.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::I2c`] if I2C returns an error. The original SCRATCH
+    /// value is still restored if possible.
+    ///
+    pub fn self_test(&mut self) -> Result<SelfTestReport, Error<E>> {
+        let original = self.get_scratch()?;
+
+        let patterns = self.check_scratch_patterns();
+        let restored = self.set_scratch(original).and_then(|()| self.get_scratch());
+        let (walking_ones, walking_zeros, alternating) = patterns?;
+        let scratch_restored = restored? == original;
+
+        let version = self.get_version()?.variant() != Variant::Unknown;
+        let control_reserved_bits = self.read_byte(REG_CONTROL)? & CONTROL_RESERVED_MASK == 0;
+
+        Ok(SelfTestReport {
+            walking_ones,
+            walking_zeros,
+            alternating,
+            scratch_restored,
+            version,
+            control_reserved_bits,
+        })
+    }
+
+    /// Writes each group of test patterns to SCRATCH and reads them back.
+    ///
+    /// Returns whether the walking-ones, walking-zeros, and alternating
+    /// patterns were read back unchanged.
+    ///
+    fn check_scratch_patterns(&mut self) -> Result<(bool, bool, bool), Error<E>> {
+        let mut walking_ones = true;
+        let mut walking_zeros = true;
+        for bit in 0..8 {
+            walking_ones &= self.check_scratch_pattern(1 << bit)?;
+            walking_zeros &= self.check_scratch_pattern(!(1 << bit))?;
+        }
+
+        let mut alternating = true;
+        for pattern in ALTERNATING_PATTERNS {
+            alternating &= self.check_scratch_pattern(pattern)?;
+        }
+
+        Ok((walking_ones, walking_zeros, alternating))
+    }
+
+    /// Writes `pattern` to SCRATCH and returns true if it reads back
+    /// unchanged.
+    ///
+    fn check_scratch_pattern(&mut self, pattern: u8) -> Result<bool, Error<E>> {
+        self.write_byte(REG_SCRATCH, pattern)?;
+        Ok(self.read_byte(REG_SCRATCH)? == pattern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SelfTestReport;
+    use crate::mock::{with_bus_error, I2cMock, I2cTransaction};
+    use crate::{PaSpl, DEVICE_ADDR_DEFAULT, REG_CONTROL, REG_SCRATCH, REG_VERSION};
+
+    /// Expectations for writing `pattern` to SCRATCH and reading back `read`.
+    fn pattern_expectations(pattern: u8, read: u8) -> [I2cTransaction; 2] {
+        [
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_SCRATCH, pattern]),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_SCRATCH], vec![read]),
+        ]
+    }
+
+    /// Expectations for the pattern sequence, with SCRATCH bit `stuck_bit`
+    /// stuck high if given.
+    fn patterns_expectations(stuck_bit: Option<u8>) -> Vec<I2cTransaction> {
+        let stuck = stuck_bit.map_or(0, |bit| 1 << bit);
+        let mut expectations = vec![];
+        for bit in 0..8 {
+            expectations.extend(pattern_expectations(1 << bit, (1 << bit) | stuck));
+            expectations.extend(pattern_expectations(!(1 << bit), !(1 << bit) | stuck));
+        }
+        expectations.extend(pattern_expectations(0x55, 0x55 | stuck));
+        expectations.extend(pattern_expectations(0xaa, 0xaa | stuck));
+        expectations
+    }
+
+    #[test]
+    fn confirm_self_test_passes() {
+        let mut expectations = vec![I2cTransaction::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_SCRATCH],
+            vec![0x3c],
+        )];
+        expectations.extend(patterns_expectations(None));
+        expectations.extend(pattern_expectations(0x3c, 0x3c));
+        expectations.extend([
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_VERSION], vec![0x32]),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL], vec![0x02]),
+        ]);
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let report = pa_spl.self_test().unwrap();
+        assert!(report.passed());
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_self_test_reports_failures() {
+        let mut expectations = vec![I2cTransaction::write_read(
+            DEVICE_ADDR_DEFAULT,
+            vec![REG_SCRATCH],
+            vec![0x00],
+        )];
+        expectations.extend(patterns_expectations(Some(0)));
+        expectations.extend(pattern_expectations(0x00, 0x01));
+        expectations.extend([
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_VERSION], vec![0xff]),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL], vec![0x82]),
+        ]);
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        let report = pa_spl.self_test().unwrap();
+        assert_eq!(
+            SelfTestReport {
+                walking_ones: false,
+                walking_zeros: false,
+                alternating: false,
+                scratch_restored: false,
+                version: false,
+                control_reserved_bits: false,
+            },
+            report
+        );
+        assert!(!report.passed());
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_self_test_restores_scratch_after_bus_error() {
+        let expectations = vec![
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_SCRATCH], vec![0xaa]),
+            with_bus_error(I2cTransaction::write(
+                DEVICE_ADDR_DEFAULT,
+                vec![REG_SCRATCH, 0x01],
+            )),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_SCRATCH, 0xaa]),
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_SCRATCH], vec![0xaa]),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = PaSpl::new(i2c_mock);
+
+        assert!(pa_spl.self_test().is_err());
+
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+}
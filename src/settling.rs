@@ -0,0 +1,442 @@
+//! Tracking of the warm-up and settling time of readings.
+
+use defmt::Format;
+
+use crate::hal::{Delay, I2cBus};
+use crate::{AveragingTime, Config, ControlRegister, Error, PaSpl, Spectrum, WARM_UP_DELAY_MS};
+
+/// A monotonic clock counting milliseconds.
+///
+/// The count may wrap around at `u32::MAX`. Implemented for closures
+/// returning the current count, e.g. of a SysTick or RTC based timer.
+pub trait Clock {
+    /// Gets the current time in ms.
+    fn now_ms(&mut self) -> u32;
+}
+
+impl<F> Clock for F
+where
+    F: FnMut() -> u32,
+{
+    fn now_ms(&mut self) -> u32 {
+        self()
+    }
+}
+
+/// Whether a reading was taken after the module settled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Format)]
+pub enum Validity {
+    /// The module had settled, so the reading is valid.
+    Valid,
+    /// The module was still warming up or settling after a configuration
+    /// change, so the reading may be wrong.
+    Settling,
+}
+
+/// A reading tagged with its [`Validity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Format)]
+pub struct Reading<T> {
+    /// Value read from the module.
+    pub value: T,
+    /// Whether the module had settled when the value was read.
+    pub validity: Validity,
+}
+
+impl<T> Reading<T> {
+    /// Returns true if the reading is valid.
+    ///
+    pub fn is_valid(&self) -> bool {
+        self.validity == Validity::Valid
+    }
+
+    /// Gets the value if the reading is valid.
+    ///
+    pub fn valid(self) -> Option<T> {
+        if self.is_valid() {
+            Some(self.value)
+        } else {
+            None
+        }
+    }
+}
+
+/// A PA SPL Module driver that tracks when its readings are valid with the
+/// clock `C`.
+///
+/// The manual states that the decibel reading is only valid about 1 second
+/// after power-up, and that a configuration change takes at least one
+/// averaging time to take effect. This driver records the instants of
+/// power-up, reset and configuration changes made through it, and tags
+/// readings taken before the module settled as [`Validity::Settling`].
+///
+/// Changes made through [`ClockedPaSpl::driver`] are not tracked; record them
+/// with [`ClockedPaSpl::mark_power_up`] or
+/// [`ClockedPaSpl::mark_config_change`].
+///
+/// Powering down is not wrapped, since [`PaSpl::power_down`] consumes the
+/// driver. Release the driver with [`ClockedPaSpl::release`] and power it
+/// down. After [`SleepingPaSpl::wake`](crate::SleepingPaSpl::wake), wrap the
+/// woken driver again with [`ClockedPaSpl::new`], which starts the warm-up
+/// from the wake and reads the restored averaging time from the module when
+/// it is needed.
+pub struct ClockedPaSpl<I2C, C>
+where
+    I2C: I2cBus,
+{
+    driver: PaSpl<I2C>,
+    clock: C,
+    /// Instant in ms of the last power-up, reset or configuration change.
+    since_ms: u32,
+    /// Time in ms the module needs to settle from `since_ms`.
+    settle_ms: u32,
+    /// Averaging time last written through this driver, if known.
+    averaging_time: Option<AveragingTime>,
+}
+
+impl<E, I2C, C> ClockedPaSpl<I2C, C>
+where
+    I2C: I2cBus<Error = E>,
+    C: Clock,
+{
+    /// Wraps the driver `driver` of a module that has just powered up.
+    ///
+    /// Readings are settling for [`WARM_UP_DELAY_MS`] from now. Call
+    /// [`ClockedPaSpl::mark_settled`] if the module powered up earlier.
+    ///
+    pub fn new(driver: PaSpl<I2C>, clock: C) -> Self {
+        let mut clocked = Self {
+            driver,
+            clock,
+            since_ms: 0,
+            settle_ms: 0,
+            averaging_time: None,
+        };
+        clocked.since_ms = clocked.clock.now_ms();
+        clocked.settle_ms = WARM_UP_DELAY_MS;
+        clocked
+    }
+
+    /// Gets the wrapped driver for operations that are not tracked.
+    ///
+    pub fn driver(&mut self) -> &mut PaSpl<I2C> {
+        &mut self.driver
+    }
+
+    /// Destroys this driver and releases the wrapped driver and the clock.
+    ///
+    pub fn release(self) -> (PaSpl<I2C>, C) {
+        (self.driver, self.clock)
+    }
+
+    /// Gets the time in ms until the module has settled, 0 if it has.
+    ///
+    pub fn settling_remaining_ms(&mut self) -> u32 {
+        let elapsed = self.clock.now_ms().wrapping_sub(self.since_ms);
+        self.settle_ms.saturating_sub(elapsed)
+    }
+
+    /// Returns true if the module has settled, so readings are valid.
+    ///
+    pub fn is_settled(&mut self) -> bool {
+        self.settling_remaining_ms() == 0
+    }
+
+    /// Records that the module powered up or was reset now.
+    ///
+    pub fn mark_power_up(&mut self) {
+        self.settle_for(WARM_UP_DELAY_MS);
+        self.averaging_time = Some(AveragingTime::DEFAULT);
+    }
+
+    /// Records that the configuration of the module changed now.
+    ///
+    /// Readings are settling for one averaging time. The averaging time is
+    /// read from the module if it was not written through this driver.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`PaSpl::get_avg_time`] if reading the averaging
+    /// time fails.
+    ///
+    pub fn mark_config_change(&mut self) -> Result<(), Error<E>> {
+        let averaging_time = match self.averaging_time {
+            Some(averaging_time) => averaging_time,
+            None => self.driver.get_avg_time()?,
+        };
+        self.averaging_time = Some(averaging_time);
+        self.settle_for(averaging_time.ms() as u32);
+        Ok(())
+    }
+
+    /// Records that the module has already settled.
+    ///
+    pub fn mark_settled(&mut self) {
+        self.settle_ms = 0;
+    }
+
+    /// Resets the module with [`PaSpl::reset`] and records the reset.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`PaSpl::reset`].
+    ///
+    pub fn reset(&mut self) -> Result<(), Error<E>> {
+        self.driver.reset()?;
+        self.mark_power_up();
+        Ok(())
+    }
+
+    /// Sets the averaging time with [`PaSpl::set_avg_time`] and records the
+    /// configuration change.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`PaSpl::set_avg_time`].
+    ///
+    pub fn set_avg_time(&mut self, averaging_time: AveragingTime) -> Result<(), Error<E>> {
+        self.driver.set_avg_time(averaging_time)?;
+        self.averaging_time = Some(averaging_time);
+        self.mark_config_change()
+    }
+
+    /// Sets the CONTROL register with [`PaSpl::set_control_register`], e.g.
+    /// to change the filter, and records the configuration change.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`PaSpl::set_control_register`] and
+    /// [`ClockedPaSpl::mark_config_change`].
+    ///
+    pub fn set_control_register(&mut self, reg: ControlRegister) -> Result<(), Error<E>> {
+        self.driver.set_control_register(reg)?;
+        self.mark_config_change()
+    }
+
+    /// Applies the configuration `config` with [`PaSpl::apply`] and records
+    /// the configuration change, if any field was written.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`PaSpl::apply`].
+    ///
+    pub fn apply(&mut self, config: &Config) -> Result<(), Error<E>> {
+        let diff = self.driver.apply_changes(config)?;
+        self.averaging_time = Some(config.averaging_time);
+        if diff.is_empty() {
+            return Ok(());
+        }
+        self.mark_config_change()
+    }
+
+    /// Gets the latest SPL value in decibels, tagged with its validity.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`PaSpl::get_latest_decibel`].
+    ///
+    pub fn get_latest_decibel(&mut self) -> Result<Reading<u8>, Error<E>> {
+        let value = self.driver.get_latest_decibel()?;
+        Ok(self.tag(value))
+    }
+
+    /// Gets the latest SPL value in decibels if the module has settled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::NotReady`] without reading the module if it has not
+    /// settled.
+    ///
+    /// Returns the errors of [`PaSpl::get_latest_decibel`].
+    ///
+    pub fn get_valid_decibel(&mut self) -> Result<u8, Error<E>> {
+        if !self.is_settled() {
+            return Err(Error::NotReady);
+        }
+        self.driver.get_latest_decibel()
+    }
+
+    /// Blocks until the module has settled, then gets the latest SPL value in
+    /// decibels.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`PaSpl::get_latest_decibel`].
+    ///
+    pub fn wait_for_valid_decibel<D>(&mut self, delay: &mut D) -> Result<u8, Error<E>>
+    where
+        D: Delay,
+    {
+        loop {
+            let remaining_ms = self.settling_remaining_ms();
+            if remaining_ms == 0 {
+                return self.driver.get_latest_decibel();
+            }
+            delay.delay_ms(remaining_ms);
+        }
+    }
+
+    /// Reads the 64-bin audio spectrum, tagged with its validity.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`PaSpl::read_spectrum`].
+    ///
+    pub fn read_spectrum(&mut self) -> Result<Reading<Spectrum>, Error<E>> {
+        let value = self.driver.read_spectrum()?;
+        Ok(self.tag(value))
+    }
+
+    /// Starts a settling period of `ms` from now, unless the current one ends
+    /// later.
+    ///
+    fn settle_for(&mut self, ms: u32) {
+        let remaining_ms = self.settling_remaining_ms();
+        self.since_ms = self.clock.now_ms();
+        self.settle_ms = ms.max(remaining_ms);
+    }
+
+    /// Tags `value` with the current validity.
+    ///
+    fn tag<T>(&mut self, value: T) -> Reading<T> {
+        let validity = if self.is_settled() {
+            Validity::Valid
+        } else {
+            Validity::Settling
+        };
+        Reading { value, validity }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClockedPaSpl, Reading, Validity};
+    use crate::hal::Delay;
+    use crate::mock::{read_registers, I2cMock, I2cTransaction};
+    use crate::{
+        AveragingTime, Config, ControlRegister, Error, FilterSetting, PaSpl, DEVICE_ADDR_DEFAULT,
+        REG_CONTROL, REG_CONTROL_DEFAULT, REG_DECIBEL, REG_RESET, REG_TAVG_HIGH, REG_THR_MIN,
+        REG_VERSION, WARM_UP_DELAY_MS,
+    };
+    use core::cell::Cell;
+
+    /// Delay that advances the shared time instead of waiting.
+    struct ClockDelay<'a>(&'a Cell<u32>);
+
+    impl Delay for ClockDelay<'_> {
+        fn delay_ms(&mut self, ms: u32) {
+            self.0.set(self.0.get() + ms);
+        }
+    }
+
+    fn read_decibel(value: u8) -> I2cTransaction {
+        I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_DECIBEL], vec![value])
+    }
+
+    #[test]
+    fn confirm_warm_up() {
+        let now = Cell::new(5000);
+        let expectations = vec![read_decibel(0), read_decibel(62)];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = ClockedPaSpl::new(PaSpl::new(i2c_mock), || now.get());
+
+        assert_eq!(
+            Ok(Reading {
+                value: 0,
+                validity: Validity::Settling,
+            }),
+            pa_spl.get_latest_decibel()
+        );
+        now.set(5999);
+        assert_eq!(1, pa_spl.settling_remaining_ms());
+        assert_eq!(Err(Error::NotReady), pa_spl.get_valid_decibel());
+        now.set(5000 + WARM_UP_DELAY_MS);
+        assert_eq!(Ok(62), pa_spl.get_valid_decibel());
+
+        let (pa_spl, _) = pa_spl.release();
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_config_change_settles_for_averaging_time() {
+        let now = Cell::new(u32::MAX - 100);
+        let mut reg_control = ControlRegister::new();
+        reg_control.set_filter(FilterSetting::CWeighting);
+        let expectations = vec![
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_TAVG_HIGH, 0x00, 0x7D]),
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_CONTROL, 0b0000_0100]),
+            read_decibel(70),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = ClockedPaSpl::new(PaSpl::new(i2c_mock), || now.get());
+        pa_spl.mark_settled();
+        assert!(pa_spl.is_settled());
+
+        pa_spl.set_avg_time(AveragingTime::FAST).unwrap();
+        assert_eq!(125, pa_spl.settling_remaining_ms());
+        now.set(now.get().wrapping_add(100));
+        pa_spl.set_control_register(reg_control).unwrap();
+        assert_eq!(125, pa_spl.settling_remaining_ms());
+        now.set(now.get().wrapping_add(125));
+        assert_eq!(Some(70), pa_spl.get_latest_decibel().unwrap().valid());
+
+        let (pa_spl, _) = pa_spl.release();
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_no_op_apply_keeps_settled() {
+        let now = Cell::new(0);
+        let mut expectations = vec![
+            I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_VERSION], vec![0x32]),
+            I2cTransaction::write_read(
+                DEVICE_ADDR_DEFAULT,
+                vec![REG_CONTROL],
+                vec![REG_CONTROL_DEFAULT],
+            ),
+        ];
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_TAVG_HIGH,
+            vec![0x03, 0xE8],
+        ));
+        expectations.extend(read_registers(
+            DEVICE_ADDR_DEFAULT,
+            REG_THR_MIN,
+            vec![45, 85],
+        ));
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = ClockedPaSpl::new(PaSpl::new(i2c_mock), || now.get());
+        pa_spl.mark_settled();
+
+        pa_spl.apply(&Config::default()).unwrap();
+        assert!(pa_spl.is_settled());
+
+        let (pa_spl, _) = pa_spl.release();
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+
+    #[test]
+    fn confirm_wait_for_valid_decibel_after_reset() {
+        let now = Cell::new(0);
+        let expectations = vec![
+            I2cTransaction::write(DEVICE_ADDR_DEFAULT, vec![REG_RESET, 0b0000_1000]),
+            read_decibel(58),
+        ];
+        let i2c_mock = I2cMock::new(&expectations);
+        let mut pa_spl = ClockedPaSpl::new(PaSpl::new(i2c_mock), || now.get());
+        pa_spl.mark_settled();
+
+        now.set(10_000);
+        pa_spl.reset().unwrap();
+        assert!(!pa_spl.is_settled());
+        assert_eq!(Ok(58), pa_spl.wait_for_valid_decibel(&mut ClockDelay(&now)));
+        assert_eq!(10_000 + WARM_UP_DELAY_MS, now.get());
+
+        let (pa_spl, _) = pa_spl.release();
+        let mut mock = pa_spl.release();
+        mock.done();
+    }
+}
@@ -0,0 +1,114 @@
+//! Tests of the driver on a bus shared with other devices.
+
+use crate::mock::{read_registers, I2cMock, I2cTransaction};
+use crate::{PaSpl, DEVICE_ADDR_DEFAULT, REG_DECIBEL, REG_SCRATCH, REG_THR_MIN};
+use core::cell::RefCell;
+use embedded_hal::i2c::I2c;
+use embedded_hal_bus::i2c::{CriticalSectionDevice, MutexDevice, RefCellDevice};
+use std::sync::Mutex;
+
+/// Address of a second PA SPL Module with a custom address.
+const DEVICE_ADDR_CUSTOM: u8 = 0x49;
+/// Address of an unrelated device on the same bus.
+const OTHER_DEVICE_ADDR: u8 = 0x40;
+
+/// Expectations for two modules and another device taking turns on the bus.
+fn shared_expectations() -> Vec<I2cTransaction> {
+    let mut expectations = vec![
+        I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_DECIBEL], vec![61]),
+        I2cTransaction::write(OTHER_DEVICE_ADDR, vec![0xfe, 0x01]),
+        I2cTransaction::write_read(DEVICE_ADDR_CUSTOM, vec![REG_DECIBEL], vec![74]),
+    ];
+    expectations.extend(read_registers(
+        DEVICE_ADDR_CUSTOM,
+        REG_THR_MIN,
+        vec![45, 85],
+    ));
+    expectations.push(I2cTransaction::write(
+        DEVICE_ADDR_DEFAULT,
+        vec![REG_SCRATCH, 0x55],
+    ));
+    expectations
+}
+
+/// Runs the transactions of [`shared_expectations`] through two drivers and
+/// another device on the same bus.
+fn exercise<D>(a: D, b: D, mut other: D)
+where
+    D: I2c,
+    D::Error: core::fmt::Debug,
+{
+    let mut first = PaSpl::new(a);
+    let mut second = PaSpl::new(b);
+    second.set_device_addr(DEVICE_ADDR_CUSTOM);
+
+    assert_eq!(61, first.get_latest_decibel().unwrap());
+    other.write(OTHER_DEVICE_ADDR, &[0xfe, 0x01]).unwrap();
+    assert_eq!(74, second.get_latest_decibel().unwrap());
+    assert_eq!((45, 85), second.get_threshold_window().unwrap());
+    first.set_scratch(0x55).unwrap();
+}
+
+#[test]
+fn confirm_borrowed_bus() {
+    let expectations = vec![
+        I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_DECIBEL], vec![61]),
+        I2cTransaction::write(OTHER_DEVICE_ADDR, vec![0xfe, 0x01]),
+        I2cTransaction::write_read(DEVICE_ADDR_DEFAULT, vec![REG_DECIBEL], vec![62]),
+    ];
+    let mut i2c_mock = I2cMock::new(&expectations);
+
+    {
+        let mut pa_spl = PaSpl::new(&mut i2c_mock);
+        assert_eq!(Ok(61), pa_spl.get_latest_decibel());
+    }
+
+    // The driver only borrowed the bus, so the bus can be used directly again.
+    i2c_mock.write(OTHER_DEVICE_ADDR, &[0xfe, 0x01]).unwrap();
+
+    {
+        let mut pa_spl = PaSpl::new(&mut i2c_mock);
+        assert_eq!(Ok(62), pa_spl.get_latest_decibel());
+    }
+
+    i2c_mock.done();
+}
+
+#[test]
+fn confirm_refcell_device() {
+    let bus = RefCell::new(I2cMock::new(&shared_expectations()));
+
+    exercise(
+        RefCellDevice::new(&bus),
+        RefCellDevice::new(&bus),
+        RefCellDevice::new(&bus),
+    );
+
+    bus.into_inner().done();
+}
+
+#[test]
+fn confirm_critical_section_device() {
+    let bus = critical_section::Mutex::new(RefCell::new(I2cMock::new(&shared_expectations())));
+
+    exercise(
+        CriticalSectionDevice::new(&bus),
+        CriticalSectionDevice::new(&bus),
+        CriticalSectionDevice::new(&bus),
+    );
+
+    bus.into_inner().into_inner().done();
+}
+
+#[test]
+fn confirm_mutex_device() {
+    let bus = Mutex::new(I2cMock::new(&shared_expectations()));
+
+    exercise(
+        MutexDevice::new(&bus),
+        MutexDevice::new(&bus),
+        MutexDevice::new(&bus),
+    );
+
+    bus.into_inner().unwrap().done();
+}
@@ -0,0 +1,99 @@
+//! Audio spectrum read from the FREQ_64BINS_0 to FREQ_64BINS_63 registers.
+
+use defmt::Format;
+
+/// Number of FREQ_64BINS registers.
+pub const SPECTRUM_BINS: usize = 64;
+
+/// Total bandwidth of the spectrum analyzer in Hz.
+pub const SPECTRUM_BANDWIDTH_HZ: f32 = 8_000.0;
+
+/// Width of each frequency bin in Hz.
+pub const SPECTRUM_BIN_WIDTH_HZ: f32 = SPECTRUM_BANDWIDTH_HZ / SPECTRUM_BINS as f32;
+
+/// Audio spectrum captured by the spectrum analyzer variant of the module.
+///
+/// The spectrum is split into 64 equal bands across 8 kHz, so each bin is
+/// 125 Hz wide. Bin values are the magnitude of the frequency component in
+/// dB SPL. No weighting filter is applied to frequency data and the values are
+/// exponentially averaged over the Tavg period.
+#[derive(Debug, Clone, PartialEq, Eq, Format)]
+pub struct Spectrum {
+    bins: [u8; SPECTRUM_BINS],
+}
+
+impl Spectrum {
+    /// Creates a spectrum from the values of FREQ_64BINS_0 to FREQ_64BINS_63.
+    ///
+    pub(crate) fn from_bins(bins: [u8; SPECTRUM_BINS]) -> Self {
+        Self { bins }
+    }
+
+    /// Gets the bin values in dB SPL, lowest frequency first.
+    ///
+    pub fn bins(&self) -> &[u8; SPECTRUM_BINS] {
+        &self.bins
+    }
+
+    /// Gets the value in dB SPL of bin `index`.
+    ///
+    /// Returns `None` if `index` is not less than [`SPECTRUM_BINS`].
+    ///
+    pub fn get(&self, index: usize) -> Option<u8> {
+        self.bins.get(index).copied()
+    }
+
+    /// Gets the center frequency in Hz of bin `index`.
+    ///
+    /// Returns `None` if `index` is not less than [`SPECTRUM_BINS`].
+    ///
+    pub fn center_frequency_hz(index: usize) -> Option<f32> {
+        if index < SPECTRUM_BINS {
+            Some(bin_center_hz(index))
+        } else {
+            None
+        }
+    }
+
+    /// Gets the index of the bin whose band contains `frequency_hz`.
+    ///
+    /// Returns `None` if the frequency is outside of the 0 Hz to 8 kHz band.
+    ///
+    pub fn bin_index(frequency_hz: f32) -> Option<usize> {
+        if (0.0..SPECTRUM_BANDWIDTH_HZ).contains(&frequency_hz) {
+            Some((frequency_hz / SPECTRUM_BIN_WIDTH_HZ) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Gets the center frequency in Hz and value in dB SPL of the loudest bin.
+    ///
+    /// Ties resolve to the lowest frequency.
+    ///
+    pub fn peak(&self) -> (f32, u8) {
+        let mut peak = 0;
+        for (index, value) in self.bins.iter().enumerate() {
+            if *value > self.bins[peak] {
+                peak = index;
+            }
+        }
+
+        (bin_center_hz(peak), self.bins[peak])
+    }
+
+    /// Iterates over the bins as pairs of center frequency in Hz and value in
+    /// dB SPL, lowest frequency first.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = (f32, u8)> + '_ {
+        self.bins
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (bin_center_hz(index), *value))
+    }
+}
+
+/// Gets the center frequency in Hz of bin `index`.
+fn bin_center_hz(index: usize) -> f32 {
+    (index as f32 + 0.5) * SPECTRUM_BIN_WIDTH_HZ
+}
@@ -0,0 +1,131 @@
+//! Hardware/firmware version and product variant decoded from the VERSION
+//! register.
+
+use defmt::Format;
+
+/// Product variant of the module.
+///
+/// The variant is identified by the value of the VERSION register, as listed
+/// in the firmware version notes of the vendor programming manual.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Format)]
+pub enum Variant {
+    /// Built-in MEMS microphone, long term support (VERSION = 0x31).
+    MemsLts,
+    /// Built-in MEMS microphone with the audio spectrum analyzer
+    /// (VERSION = 0x32, and 0x33 as seen on modules in the field).
+    MemsSpectrumAnalyzer,
+    /// External IP67 electret microphone (VERSION = 0x81).
+    ExternalMic,
+    /// A VERSION value not documented by the vendor.
+    Unknown,
+}
+
+impl Variant {
+    /// Returns true if the variant has the audio spectrum analyzer
+    /// (FREQ_64BINS registers).
+    ///
+    pub const fn has_spectrum(self) -> bool {
+        matches!(self, Self::MemsSpectrumAnalyzer)
+    }
+
+    /// Returns true if the variant has adjustable microphone gain (GAIN
+    /// register).
+    ///
+    pub const fn has_gain(self) -> bool {
+        matches!(self, Self::ExternalMic)
+    }
+
+    /// Returns true if the variant has a line output (Enable Line Output bit
+    /// of the CONTROL register).
+    ///
+    pub const fn has_line_out(self) -> bool {
+        matches!(self, Self::ExternalMic)
+    }
+}
+
+/// Value of the VERSION register.
+///
+/// Bits `[7:4]` hold the hardware version and bits `[3:0]` hold the firmware
+/// version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Format)]
+pub struct Version(u8);
+
+impl Version {
+    /// Creates a version from the raw value of the VERSION register.
+    ///
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    /// Gets the raw value of the VERSION register.
+    ///
+    pub const fn into_bits(self) -> u8 {
+        self.0
+    }
+
+    /// Gets the hardware version from bits `[7:4]`.
+    ///
+    pub const fn hardware(self) -> u8 {
+        self.0 >> 4
+    }
+
+    /// Gets the firmware version from bits `[3:0]`.
+    ///
+    pub const fn firmware(self) -> u8 {
+        self.0 & 0x0f
+    }
+
+    /// Returns true if the hardware version is one listed in the vendor
+    /// programming manual: 0x3 for the built-in MEMS microphone or 0x8 for the
+    /// external microphone.
+    ///
+    pub const fn is_known_hardware(self) -> bool {
+        matches!(self.hardware(), 0x3 | 0x8)
+    }
+
+    /// Gets the product variant identified by the version.
+    ///
+    /// Only the VERSION values listed for [`Variant`] are identified. Any
+    /// other value, including later firmware on a known hardware version, is
+    /// [`Variant::Unknown`], since the features of undocumented firmware are
+    /// not known.
+    ///
+    pub const fn variant(self) -> Variant {
+        match self.0 {
+            0x31 => Variant::MemsLts,
+            0x32 | 0x33 => Variant::MemsSpectrumAnalyzer,
+            0x81 => Variant::ExternalMic,
+            _ => Variant::Unknown,
+        }
+    }
+
+    /// Returns true if the module has the audio spectrum analyzer.
+    ///
+    pub const fn has_spectrum(self) -> bool {
+        self.variant().has_spectrum()
+    }
+
+    /// Returns true if the module has adjustable microphone gain.
+    ///
+    pub const fn has_gain(self) -> bool {
+        self.variant().has_gain()
+    }
+
+    /// Returns true if the module has a line output.
+    ///
+    pub const fn has_line_out(self) -> bool {
+        self.variant().has_line_out()
+    }
+}
+
+impl From<u8> for Version {
+    fn from(bits: u8) -> Self {
+        Self::from_bits(bits)
+    }
+}
+
+impl From<Version> for u8 {
+    fn from(version: Version) -> Self {
+        version.into_bits()
+    }
+}
@@ -6,12 +6,11 @@
 /// This HIL suite is configured to run minimal HIL tests using a STM32F3
 /// Discovery development board.
 
-use defmt::Format;
 use defmt_rtt as _; // defmt transport.
 use panic_probe as _; // Panic handler.
 use stm32f3xx_hal as _; // Memory layout.
 
-use pa_spl::PaSpl;
+use pa_spl::{hal::Eh0, PaSpl};
 use stm32f3xx_hal::gpio::{
     gpiob::{PB6, PB7},
     Alternate, OpenDrain,
@@ -19,37 +18,16 @@ use stm32f3xx_hal::gpio::{
 use stm32f3xx_hal::{i2c::I2c, pac};
 
 struct State {
-    pa_spl: PaSpl<I2c<pac::I2C1, (PB6<Alternate<OpenDrain, 4>>, PB7<Alternate<OpenDrain, 4>>)>>,
-}
-
-/// Define a newtype wrapper for Error<E>.
-///
-pub struct WrappedError<E>(pub pa_spl::Error<E>);
-
-/// Implement Format for WrappedError<E>.
-///
-impl<E: Format> Format for WrappedError<E> {
-    fn format(&self, f: defmt::Formatter) {
-        match &self.0 {
-            pa_spl::Error::I2c(err) => {
-                defmt::write!(f, "I2C Error: {:?}", err);
-            }
-            pa_spl::Error::NoI2cInstance => {
-                defmt::write!(f, "No I2C Instance available");
-            }
-            pa_spl::Error::BufferOverflow => {
-                defmt::write!(f, "Buffer has overflowed");
-            }
-        }
-    }
+    pa_spl:
+        PaSpl<Eh0<I2c<pac::I2C1, (PB6<Alternate<OpenDrain, 4>>, PB7<Alternate<OpenDrain, 4>>)>>>,
 }
 
 #[defmt_test::tests]
 mod tests {
-    use super::State;
+    use super::{Eh0, State};
     use crate::delay_ms;
     use defmt::{assert_eq, unwrap};
-    use pa_spl::{ControlRegister, FilterSetting, PaSpl, REG_CONTROL_DEFAULT};
+    use pa_spl::{AveragingTime, ControlRegister, FilterSetting, PaSpl, Variant, REG_CONTROL_DEFAULT};
     use stm32f3xx_hal::{i2c::I2c, pac, prelude::*};
 
     #[init]
@@ -88,7 +66,7 @@ mod tests {
             &mut rcc.apb1,
         );
 
-        let pa_spl = PaSpl::new(i2c);
+        let pa_spl = PaSpl::new(Eh0(i2c));
         State { pa_spl }
     }
 
@@ -106,10 +84,16 @@ mod tests {
         // NOTE: The published version is 0x32 but this device returns 0x33.
         //
         const EXPECTED: u8 = 0x33;
-        let firmware_version = state.pa_spl.get_firmware_version().unwrap();
+        let firmware_version = unwrap!(state.pa_spl.get_firmware_version());
         assert_eq!(EXPECTED, firmware_version);
     }
 
+    #[test]
+    fn confirm_variant(state: &mut State) {
+        let variant = unwrap!(state.pa_spl.get_variant());
+        assert_eq!(Variant::MemsSpectrumAnalyzer, variant);
+    }
+
     #[test]
     fn confirm_device_id(state: &mut State) {
         // The device ID is not published and will differ from device to device,
@@ -121,15 +105,15 @@ mod tests {
 
     #[test]
     fn confirm_get_avg_time(state: &mut State) {
-        const EXPECTED: u16 = 1000;
-        let avg_time = state.pa_spl.get_avg_time().unwrap();
+        const EXPECTED: AveragingTime = AveragingTime::SLOW;
+        let avg_time = unwrap!(state.pa_spl.get_avg_time());
         assert_eq!(EXPECTED, avg_time);
     }
 
     #[test]
     fn confirm_get_control_register(state: &mut State) {
         const EXPECTED: ControlRegister = ControlRegister::from_bits(REG_CONTROL_DEFAULT);
-        let reg_control = state.pa_spl.get_control_register().unwrap();
+        let reg_control = unwrap!(state.pa_spl.get_control_register());
         assert_eq!(EXPECTED, reg_control);
     }
 
@@ -173,7 +157,22 @@ mod tests {
         // Confirm that the settings have been reset to the default.
         //
         const EXPECTED: ControlRegister = ControlRegister::from_bits(REG_CONTROL_DEFAULT);
-        let reg_control = state.pa_spl.get_control_register().unwrap();
+        let reg_control = unwrap!(state.pa_spl.get_control_register());
+        assert_eq!(EXPECTED, reg_control);
+    }
+
+    #[test]
+    fn confirm_clear_min_max_and_history(state: &mut State) {
+        let result = state.pa_spl.clear_min_max();
+        assert!(result.is_ok());
+
+        let result = state.pa_spl.clear_history();
+        assert!(result.is_ok());
+
+        // Clearing must not touch the configuration.
+        //
+        const EXPECTED: ControlRegister = ControlRegister::from_bits(REG_CONTROL_DEFAULT);
+        let reg_control = unwrap!(state.pa_spl.get_control_register());
         assert_eq!(EXPECTED, reg_control);
     }
 
@@ -183,25 +182,25 @@ mod tests {
         let write_result = state.pa_spl.set_scratch(EXPECTED_VAL);
         assert!(write_result.is_ok());
 
-        let val = state.pa_spl.get_scratch().unwrap();
+        let val = unwrap!(state.pa_spl.get_scratch());
         assert_eq!(EXPECTED_VAL, val);
     }
 
     #[test]
     fn confirm_set_avg_time(state: &mut State) {
-        let new_avg_time_ms: u16 = 125;
-        let result = state.pa_spl.set_avg_time(new_avg_time_ms);
+        let new_avg_time = AveragingTime::FAST;
+        let result = state.pa_spl.set_avg_time(new_avg_time);
         assert!(result.is_ok());
 
-        const EXPECTED: u16 = 125;
-        let avg_time = state.pa_spl.get_avg_time().unwrap();
+        const EXPECTED: AveragingTime = AveragingTime::FAST;
+        let avg_time = unwrap!(state.pa_spl.get_avg_time());
         assert_eq!(EXPECTED, avg_time);
     }
 
     #[test]
     fn confirm_set_control_register(state: &mut State) {
         const EXPECTED_DEFAULT: ControlRegister = ControlRegister::from_bits(REG_CONTROL_DEFAULT);
-        let mut reg_control = state.pa_spl.get_control_register().unwrap();
+        let mut reg_control = unwrap!(state.pa_spl.get_control_register());
         assert_eq!(EXPECTED_DEFAULT, reg_control);
 
         reg_control.set_filter(FilterSetting::CWeighting);
@@ -209,7 +208,7 @@ mod tests {
         assert!(result.is_ok());
 
         const EXPECTED_SET: ControlRegister = ControlRegister::from_bits(0b0000_0100);
-        let reg_control_set = state.pa_spl.get_control_register().unwrap();
+        let reg_control_set = unwrap!(state.pa_spl.get_control_register());
         assert_eq!(EXPECTED_SET, reg_control_set);
     }
 